@@ -1,13 +1,69 @@
-use iroh::{fields::TextInputField, kinds::ConsFields, stores::VecContainer, Kind, *};
+use iced::pane_grid::Axis;
+use iroh::{
+    codec::RonCodec,
+    fields::{
+        EnumField, NestedField, NumberRange, OptionField, PrismField, TextInputField, VecField,
+    },
+    kinds::ConsFields,
+    lens::RootLens,
+    panes::PaneLayout,
+    stores::VecContainer,
+    *,
+};
+use serde::{Deserialize, Serialize};
 
 #[macro_use]
 extern crate iroh_codegen;
 
 /// Example kind
-#[derive(Clone, Debug, Lens)]
+#[derive(Clone, Debug, Kind, Serialize, Deserialize)]
+#[iroh(key = RectId)]
 pub struct Rect {
+    #[iroh(field = Slider)]
     width: f32,
+    #[iroh(field = Spinner)]
     height: f32,
+    #[iroh(field = Checkbox)]
+    filled: bool,
+    #[iroh(field = Color)]
+    color: [u8; 4],
+    #[iroh(field = PickList)]
+    corners: Corners,
+    #[iroh(field = OutlineField)]
+    outline: Outline,
+    #[iroh(field = OriginField)]
+    origin: Vec2,
+    #[iroh(field = TagsField)]
+    tags: Vec<String>,
+    #[iroh(field = LabelField)]
+    label: Option<String>,
+    #[iroh(field = TextArea, lines = 4)]
+    notes: String,
+    #[iroh(field = Reference)]
+    attached_to: Option<RectId>,
+}
+
+/// A position
+#[derive(Clone, Debug, Default, Lens, Serialize, Deserialize)]
+pub struct Vec2 {
+    x: f32,
+    y: f32,
+}
+
+/// How the corners of a rect are drawn
+#[derive(Clone, Debug, PartialEq, Eq, Pickable, Serialize, Deserialize)]
+pub enum Corners {
+    Square,
+    Rounded,
+    Bevelled,
+}
+
+/// How the edge of a rect is drawn
+#[derive(Clone, Debug, Prism, Variants, Serialize, Deserialize)]
+pub enum Outline {
+    None,
+    Solid { width: f32 },
+    Dashed { width: f32, gap: f32 },
 }
 
 impl Default for Rect {
@@ -15,31 +71,68 @@ impl Default for Rect {
         Self {
             width: 1.0,
             height: 1.0,
+            filled: false,
+            color: [255, 255, 255, 255],
+            corners: Corners::Square,
+            outline: Outline::None,
+            origin: Vec2::default(),
+            tags: vec![],
+            label: None,
+            notes: String::new(),
+            attached_to: None,
         }
     }
 }
-impl Kind for Rect {
-    type Key = RectId;
-    type Field = ConsFields<
-        TextInputField<"Width", RectWidthLens>,
-        TextInputField<"Height", RectHeightLens>,
-    >;
-}
 
-/// The key for our example kind
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
-pub struct RectId(pub usize);
+/// Edits the origin's coordinates in a group
+type OriginField<const N: &'static str, L> =
+    NestedField<N, L, ConsFields<TextInputField<"X", Vec2XLens>, TextInputField<"Y", Vec2YLens>>>;
 
-impl Key for RectId {
-    fn first() -> Self {
-        RectId(0)
-    }
+/// Picks the kind of outline, and edits the fields of the one picked
+type OutlineField<const N: &'static str, L> = EnumField<
+    N,
+    L,
+    ConsFields<
+        PrismField<OutlineSolidWidthPrism, TextInputField<"Width", RootLens<f32>>>,
+        ConsFields<
+            PrismField<OutlineDashedWidthPrism, TextInputField<"Width", RootLens<f32>>>,
+            PrismField<OutlineDashedGapPrism, TextInputField<"Gap", RootLens<f32>>>,
+        >,
+    >,
+>;
 
-    fn next(last: &Self) -> Self {
-        RectId(last.0 + 1)
-    }
+/// Edits each tag with a text input
+type TagsField<const N: &'static str, L> = VecField<N, L, TextInputField<"Tag", RootLens<String>>>;
+
+/// Edits the label with a text input, if there is one
+type LabelField<const N: &'static str, L> =
+    OptionField<N, L, TextInputField<"Text", RootLens<String>>>;
+
+impl NumberRange for RectWidthLens {
+    const MIN: f32 = 0.0;
+    const MAX: f32 = 100.0;
+    const STEP: f32 = 0.5;
 }
 
+impl NumberRange for RectHeightLens {
+    const MIN: f32 = 0.0;
+    const MAX: f32 = 100.0;
+    const STEP: f32 = 0.5;
+}
+
+/// The key for our example kind
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Key, Serialize, Deserialize)]
+pub struct RectId(pub usize);
+
 fn main() {
-    App::<Rect, VecContainer<_>>::run(Settings::default()).unwrap();
+    AppBuilder::<Rect, VecContainer<_>, RonCodec<_>>::new()
+        .title("Rect editor")
+        .layout(PaneLayout::Split {
+            axis: Axis::Vertical,
+            ratio: 0.3,
+            a: Box::new(PaneLayout::Pane("Outline")),
+            b: Box::new(PaneLayout::Pane("Inspector")),
+        })
+        .run()
+        .unwrap();
 }
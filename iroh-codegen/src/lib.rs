@@ -4,14 +4,21 @@ use convert_case::{Case, Casing};
 use proc_macro::{self, TokenStream};
 use proc_macro2::{Ident, TokenStream as TokenStream2};
 use quote::{format_ident, quote, ToTokens};
-use syn::{parse_macro_input, DeriveInput, FieldsNamed, FieldsUnnamed, Index, Type};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input,
+    punctuated::Punctuated,
+    Attribute, DeriveInput, FieldsNamed, FieldsUnnamed, Generics, Index, Lit, Token, Type,
+};
 
 /// Generate lenses for each field in a struct
+/// Lenses for generic structs have the same generic parameters as the struct.
 /// Currently, this doesn't support enums or unions.
 #[proc_macro_derive(Lens)]
 pub fn lens(input: TokenStream) -> TokenStream {
     let DeriveInput {
         ident: struct_name,
+        generics,
         data,
         ..
     } = parse_macro_input!(input);
@@ -33,10 +40,10 @@ pub fn lens(input: TokenStream) -> TokenStream {
 
             idents
                 .into_iter()
-                .map(|(i, ty)| LensDesc::from_field(i, ty, struct_name.clone()))
+                .map(|(i, ty)| LensDesc::from_field(i, ty, struct_name.clone(), generics.clone()))
         }
         syn::Data::Enum(_) => {
-            panic!("Cannot derive lenses for an enum")
+            panic!("Cannot derive lenses for an enum, derive Prism instead")
         }
         syn::Data::Union(_) => {
             todo!()
@@ -57,10 +64,16 @@ struct LensDesc {
     ty: Type,
     name: Ident,
     target: Ident,
+    generics: Generics,
 }
 
 impl LensDesc {
-    fn from_field(field: Box<dyn ToTokens>, ty: Type, struct_name: Ident) -> Self {
+    fn from_field(
+        field: Box<dyn ToTokens>,
+        ty: Type,
+        struct_name: Ident,
+        generics: Generics,
+    ) -> Self {
         let lens_name = format_ident!(
             "{}{}Lens",
             struct_name,
@@ -71,6 +84,7 @@ impl LensDesc {
             ty,
             name: lens_name,
             target: struct_name,
+            generics,
         }
     }
 
@@ -80,21 +94,482 @@ impl LensDesc {
             ty,
             name,
             target,
+            generics,
         } = self;
+
+        // The lifetime on the methods is named so it can't shadow one of the struct's
+        let decl = optic_struct(&name, &target, &generics);
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
         quote! {
-            #[derive(Debug, Clone)]
-            pub struct #name;
-            impl iroh::lens::Lens for #name {
-                type Source = #target;
+            #decl
+
+            impl #impl_generics iroh::lens::Lens for #name #ty_generics #where_clause {
+                type Source = #target #ty_generics;
                 type Target = #ty;
 
-                fn get<'a>(source: &'a Self::Source) -> &'a Self::Target {
+                fn get<'iroh_lens>(source: &'iroh_lens Self::Source) -> &'iroh_lens Self::Target {
                     &source.#field
                 }
-                fn get_mut<'a>(source: &'a mut Self::Source) -> &'a mut Self::Target {
+                fn get_mut<'iroh_lens>(
+                    source: &'iroh_lens mut Self::Source,
+                ) -> &'iroh_lens mut Self::Target {
                     &mut source.#field
                 }
             }
         }
     }
 }
+
+/// Declare the struct for a lens or prism called `name`, which focuses on part of `target`.
+/// If `target` is generic, so is the struct, with the same parameters.
+fn optic_struct(name: &Ident, target: &Ident, generics: &Generics) -> TokenStream2 {
+    if generics.params.is_empty() {
+        return quote! {
+            #[derive(Debug, Clone)]
+            pub struct #name;
+        };
+    }
+
+    // The parameters are only used through PhantomData, so the struct is still Send, and Debug
+    // and Clone are implemented by hand so they don't need the parameters to be.
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        pub struct #name #generics (
+            std::marker::PhantomData<fn() -> #target #ty_generics>
+        ) #where_clause;
+
+        impl #impl_generics std::fmt::Debug for #name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(stringify!(#name))
+            }
+        }
+
+        impl #impl_generics Clone for #name #ty_generics #where_clause {
+            fn clone(&self) -> Self {
+                Self(std::marker::PhantomData)
+            }
+        }
+    }
+}
+
+/// Generate prisms for each field of each variant of an enum.
+/// They're named like lenses, with the variant's name after the enum's, such as `ShapeCircleRadiusPrism`.
+/// Prisms for generic enums have the same generic parameters as the enum.
+#[proc_macro_derive(Prism)]
+pub fn prism(input: TokenStream) -> TokenStream {
+    let DeriveInput {
+        ident: enum_name,
+        generics,
+        data,
+        ..
+    } = parse_macro_input!(input);
+
+    let variants = match data {
+        syn::Data::Enum(e) => e.variants,
+        _ => panic!("Prism can only be derived for enums, derive Lens instead"),
+    };
+
+    let prisms = variants.into_iter().flat_map(|v| {
+        let fields: Vec<(Box<dyn ToTokens>, Type)> = match v.fields {
+            syn::Fields::Named(FieldsNamed { named, .. }) => named
+                .into_iter()
+                .map(|f| (Box::new(f.ident) as Box<dyn ToTokens>, f.ty))
+                .collect(),
+            syn::Fields::Unnamed(FieldsUnnamed { unnamed, .. }) => unnamed
+                .into_iter()
+                .enumerate()
+                .map(|(i, f)| (Box::new(Index::from(i)) as Box<dyn ToTokens>, f.ty))
+                .collect(),
+            syn::Fields::Unit => vec![],
+        };
+
+        let enum_name = enum_name.clone();
+        let generics = generics.clone();
+        let variant = v.ident;
+        fields.into_iter().map(move |(field, ty)| {
+            let name = format_ident!(
+                "{}{}{}Prism",
+                enum_name,
+                variant,
+                format!("{}", field.to_token_stream()).to_case(Case::Pascal)
+            );
+
+            // Tuple variants can be matched like structs with numbered fields.
+            // As with lenses, the lifetime on the methods is named so it can't shadow one of the enum's.
+            let decl = optic_struct(&name, &enum_name, &generics);
+            let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+            quote! {
+                #decl
+
+                impl #impl_generics iroh::lens::Prism for #name #ty_generics #where_clause {
+                    type Source = #enum_name #ty_generics;
+                    type Target = #ty;
+
+                    #[allow(unreachable_patterns)]
+                    fn get<'iroh_lens>(
+                        source: &'iroh_lens Self::Source,
+                    ) -> Option<&'iroh_lens Self::Target> {
+                        match source {
+                            #enum_name::#variant { #field: x, .. } => Some(x),
+                            _ => None,
+                        }
+                    }
+
+                    #[allow(unreachable_patterns)]
+                    fn get_mut<'iroh_lens>(
+                        source: &'iroh_lens mut Self::Source,
+                    ) -> Option<&'iroh_lens mut Self::Target> {
+                        match source {
+                            #enum_name::#variant { #field: x, .. } => Some(x),
+                            _ => None,
+                        }
+                    }
+                }
+            }
+        })
+    });
+
+    let output = quote! {
+    #(#prisms)*
+    };
+
+    output.into()
+}
+
+/// Implement `Pickable` for an enum without fields, so it can be used with a `PickListField`.
+/// Variants are listed in the order they're declared, and named by splitting their identifier into words.
+/// Use `#[iroh(label = "...")]` on a variant to name it something else.
+#[proc_macro_derive(Pickable, attributes(iroh))]
+pub fn pickable(input: TokenStream) -> TokenStream {
+    let DeriveInput {
+        ident: enum_name,
+        data,
+        ..
+    } = parse_macro_input!(input);
+
+    let variants = match data {
+        syn::Data::Enum(e) => e.variants,
+        _ => panic!("Pickable can only be derived for enums"),
+    };
+
+    let (idents, labels): (Vec<_>, Vec<_>) = variants
+        .into_iter()
+        .map(|v| {
+            if !matches!(v.fields, syn::Fields::Unit) {
+                panic!("Pickable can only be derived for enums without fields");
+            }
+
+            let label = attr_str(&v.attrs, "label")
+                .unwrap_or_else(|| v.ident.to_string().to_case(Case::Title));
+
+            (v.ident, label)
+        })
+        .unzip();
+
+    let output = quote! {
+        impl iroh::fields::Pickable for #enum_name {
+            const VARIANTS: &'static [Self] = &[#(Self::#idents),*];
+
+            fn name(&self) -> &'static str {
+                match self {
+                    #(Self::#idents => #labels),*
+                }
+            }
+        }
+    };
+
+    output.into()
+}
+
+/// Implement `Variants` for an enum, so it can be used with an `EnumField`.
+/// Switching to a variant fills its fields with their defaults.
+/// Variants are named like `Pickable`, and `#[iroh(label = "...")]` can be used in the same way.
+/// Generic enums need bounds making them `'static + Clone + Debug + Send`, and their fields `Default`.
+#[proc_macro_derive(Variants, attributes(iroh))]
+pub fn variants(input: TokenStream) -> TokenStream {
+    let DeriveInput {
+        ident: enum_name,
+        data,
+        generics,
+        ..
+    } = parse_macro_input!(input);
+
+    let variants = match data {
+        syn::Data::Enum(e) => e.variants,
+        _ => panic!("Variants can only be derived for enums"),
+    };
+
+    let mut idents = vec![];
+    let mut labels = vec![];
+    let mut defaults = vec![];
+    for v in variants {
+        labels.push(
+            attr_str(&v.attrs, "label").unwrap_or_else(|| v.ident.to_string().to_case(Case::Title)),
+        );
+
+        let ident = v.ident;
+        defaults.push(match v.fields {
+            syn::Fields::Named(FieldsNamed { named, .. }) => {
+                let fields = named.into_iter().map(|f| f.ident);
+                quote! { Self::#ident { #(#fields: Default::default()),* } }
+            }
+            syn::Fields::Unnamed(FieldsUnnamed { unnamed, .. }) => {
+                let fields = unnamed.iter().map(|_| quote! { Default::default() });
+                quote! { Self::#ident(#(#fields),*) }
+            }
+            syn::Fields::Unit => quote! { Self::#ident },
+        });
+        idents.push(ident);
+    }
+
+    let indices = 0..idents.len();
+    let default_indices = indices.clone();
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let output = quote! {
+        impl #impl_generics iroh::fields::Variants for #enum_name #ty_generics #where_clause {
+            const NAMES: &'static [&'static str] = &[#(#labels),*];
+
+            fn variant(&self) -> usize {
+                match self {
+                    #(Self::#idents { .. } => #indices),*
+                }
+            }
+
+            fn default_variant(index: usize) -> Self {
+                match index {
+                    #(#default_indices => #defaults,)*
+                    _ => panic!("no variant {}", index),
+                }
+            }
+        }
+    };
+
+    output.into()
+}
+
+/// Implement `Kind` for a struct, generating a lens for each field and a `ConsFields` chain of fields to edit them with.
+/// Don't derive `Lens` as well, since the lenses would be generated twice.
+///
+/// The struct needs `#[iroh(key = KeyType)]`. Each field can have these attributes:
+///  - `#[iroh(label = "...")]` to name it something other than its identifier split into words.
+///  - `#[iroh(field = Slider)]` to choose the field used to edit it. This is one of `TextInput` (the default),
+///    `TextArea`, `Slider`, `Spinner`, `Checkbox`, `PickList`, `Color` or `Reference`, or any other type taking a
+///    label and a lens as generic parameters, such as an alias for a `NestedField`.
+///  - `#[iroh(lines = 4)]` to set how tall a `TextArea` is.
+///  - `#[iroh(skip)]` to leave it out of the editor.
+#[proc_macro_derive(Kind, attributes(iroh))]
+pub fn kind(input: TokenStream) -> TokenStream {
+    let DeriveInput {
+        ident: struct_name,
+        attrs,
+        data,
+        ..
+    } = parse_macro_input!(input);
+
+    let key = attr_type(&attrs, "key").expect("Expected #[iroh(key = KeyType)] on the struct");
+
+    let fields = match data {
+        syn::Data::Struct(syn::DataStruct {
+            fields: syn::Fields::Named(FieldsNamed { named, .. }),
+            ..
+        }) => named,
+        _ => panic!("Kind can only be derived for structs with named fields"),
+    };
+
+    let mut lenses = vec![];
+    let mut field_types = vec![];
+    for f in fields {
+        let ident = f.ident.clone().unwrap();
+        let desc = LensDesc::from_field(
+            Box::new(ident.clone()),
+            f.ty.clone(),
+            struct_name.clone(),
+            Generics::default(),
+        );
+        let lens_name = desc.name.clone();
+        lenses.push(desc.into_lens());
+
+        if attr_flag(&f.attrs, "skip") {
+            continue;
+        }
+
+        let label =
+            attr_str(&f.attrs, "label").unwrap_or_else(|| ident.to_string().to_case(Case::Title));
+        field_types.push(field_type(&f.attrs, &label, &lens_name));
+    }
+
+    // ConsFields<A, ConsFields<B, C>>
+    let mut field_types = field_types.into_iter().rev();
+    let last = field_types
+        .next()
+        .expect("Kind can only be derived for structs with at least one field to edit");
+    let field = field_types.fold(
+        last,
+        |rest, f| quote! { iroh::kinds::ConsFields<#f, #rest> },
+    );
+
+    let output = quote! {
+        #(#lenses)*
+
+        impl iroh::Kind for #struct_name {
+            type Key = #key;
+            type Field = #field;
+        }
+    };
+
+    output.into()
+}
+
+/// Implement `Key` for a newtype, by wrapping the key type inside it. The newtype must also implement `Clone`, `Debug` and `Eq`.
+/// Integers count up from 0, and with iroh's `uuid` feature, `Uuid`s are generated randomly.
+/// New keys come from the last one allocated, so stores don't need to look through their keys to find an unused one.
+#[proc_macro_derive(Key)]
+pub fn key(input: TokenStream) -> TokenStream {
+    let DeriveInput {
+        ident: struct_name,
+        data,
+        ..
+    } = parse_macro_input!(input);
+
+    let (field, ty): (Box<dyn ToTokens>, Type) = match data {
+        syn::Data::Struct(s) => match s.fields {
+            syn::Fields::Named(FieldsNamed { named, .. }) if named.len() == 1 => {
+                let f = named.into_iter().next().unwrap();
+                (Box::new(f.ident), f.ty)
+            }
+            syn::Fields::Unnamed(FieldsUnnamed { unnamed, .. }) if unnamed.len() == 1 => {
+                let f = unnamed.into_iter().next().unwrap();
+                (Box::new(Index::from(0)), f.ty)
+            }
+            _ => panic!("Key can only be derived for structs with one field"),
+        },
+        _ => panic!("Key can only be derived for structs with one field"),
+    };
+
+    let output = quote! {
+        impl iroh::Key for #struct_name {
+            fn first() -> Self {
+                Self {
+                    #field: <#ty as iroh::Key>::first(),
+                }
+            }
+
+            fn next(last: &Self) -> Self {
+                Self {
+                    #field: <#ty as iroh::Key>::next(&last.#field),
+                }
+            }
+        }
+    };
+
+    output.into()
+}
+
+/// The field type to edit a struct field with, from its `#[iroh(field = ...)]` attribute.
+fn field_type(attrs: &[Attribute], label: &str, lens: &Ident) -> TokenStream2 {
+    let field = match attr_type(attrs, "field") {
+        Some(Type::Path(p)) if p.qself.is_none() && p.path.get_ident().is_some() => {
+            match p.path.get_ident().unwrap().to_string().as_str() {
+                "TextInput" => quote! { iroh::fields::TextInputField },
+                "Slider" => quote! { iroh::fields::SliderField },
+                "Spinner" => quote! { iroh::fields::NumberSpinnerField },
+                "Checkbox" => quote! { iroh::fields::CheckboxField },
+                "PickList" => quote! { iroh::fields::PickListField },
+                "Color" => quote! { iroh::fields::ColorField },
+                "Reference" => quote! { iroh::fields::ReferenceField },
+                "TextArea" => {
+                    let lines = attr_int(attrs, "lines").unwrap_or(4);
+                    return quote! { iroh::fields::TextAreaField<#label, #lens, #lines> };
+                }
+                _ => p.into_token_stream(),
+            }
+        }
+        Some(ty) => ty.into_token_stream(),
+        None => quote! { iroh::fields::TextInputField },
+    };
+
+    quote! { #field<#label, #lens> }
+}
+
+/// One argument inside `#[iroh(...)]`, either `name = value` or just `name`
+struct Arg {
+    name: Ident,
+    value: Option<ArgValue>,
+}
+
+enum ArgValue {
+    Lit(Lit),
+    Type(Type),
+}
+
+impl Parse for Arg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = input.parse()?;
+        let value = if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            Some(if input.peek(Lit) {
+                ArgValue::Lit(input.parse()?)
+            } else {
+                ArgValue::Type(input.parse()?)
+            })
+        } else {
+            None
+        };
+
+        Ok(Self { name, value })
+    }
+}
+
+/// Get every argument given in `#[iroh(...)]` attributes
+fn iroh_args(attrs: &[Attribute]) -> Vec<Arg> {
+    attrs
+        .iter()
+        .filter(|a| a.path.is_ident("iroh"))
+        .flat_map(|a| {
+            a.parse_args_with(Punctuated::<Arg, Token![,]>::parse_terminated)
+                .unwrap_or_else(|e| panic!("Expected #[iroh(...)]: {}", e))
+        })
+        .collect()
+}
+
+/// Get the value of the argument `name` from a list of attributes, if it's there
+fn attr_value(attrs: &[Attribute], name: &str) -> Option<ArgValue> {
+    iroh_args(attrs)
+        .into_iter()
+        .find(|a| a.name == name)
+        .map(|a| {
+            a.value
+                .unwrap_or_else(|| panic!("Expected #[iroh({} = ...)]", name))
+        })
+}
+
+/// Get the value of `#[iroh(name = "value")]` from a list of attributes, if it's there
+fn attr_str(attrs: &[Attribute], name: &str) -> Option<String> {
+    attr_value(attrs, name).map(|v| match v {
+        ArgValue::Lit(Lit::Str(s)) => s.value(),
+        _ => panic!("Expected #[iroh({} = \"...\")]", name),
+    })
+}
+
+/// Get the value of `#[iroh(name = 123)]` from a list of attributes, if it's there
+fn attr_int(attrs: &[Attribute], name: &str) -> Option<u16> {
+    attr_value(attrs, name).map(|v| match v {
+        ArgValue::Lit(Lit::Int(i)) => i
+            .base10_parse()
+            .unwrap_or_else(|e| panic!("Expected #[iroh({} = 123)]: {}", name, e)),
+        _ => panic!("Expected #[iroh({} = 123)]", name),
+    })
+}
+
+/// Get the value of `#[iroh(name = SomeType)]` from a list of attributes, if it's there
+fn attr_type(attrs: &[Attribute], name: &str) -> Option<Type> {
+    attr_value(attrs, name).map(|v| match v {
+        ArgValue::Type(t) => t,
+        _ => panic!("Expected #[iroh({} = SomeType)]", name),
+    })
+}
+
+/// Whether `#[iroh(name)]` is in a list of attributes
+fn attr_flag(attrs: &[Attribute], name: &str) -> bool {
+    iroh_args(attrs).iter().any(|a| a.name == name)
+}
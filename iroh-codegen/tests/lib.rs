@@ -1,5 +1,12 @@
-use iroh::lens::Lens;
-use iroh_codegen::Lens;
+use iroh::{
+    fields::{
+        CheckboxField, NumberRange, Pickable, SliderField, TextAreaField, TextInputField, Variants,
+    },
+    kinds::ConsFields,
+    lens::{Lens, Prism},
+    Key, Kind,
+};
+use iroh_codegen::{Key, Kind, Lens, Pickable, Prism, Variants};
 
 #[derive(Lens)]
 pub struct Rect {
@@ -21,6 +28,32 @@ fn test_struct_lenses() {
     assert_eq!(2.0, *RectHeightLens::get(&r));
 }
 
+#[derive(Lens)]
+pub struct Pair<T, U = T>
+where
+    U: Clone,
+{
+    a: T,
+    b: U,
+}
+
+#[derive(Lens)]
+pub struct Named<'a, T: ?Sized> {
+    name: &'a T,
+}
+
+#[test]
+fn test_generic_lenses() {
+    let mut p = Pair { a: 1u8, b: "two" };
+
+    assert_eq!(1, *PairALens::get(&p));
+    *PairBLens::<u8, &str>::get_mut(&mut p) = "three";
+    assert_eq!("three", p.b);
+
+    let n = Named { name: "iroh" };
+    assert_eq!("iroh", *NamedNameLens::get(&n));
+}
+
 #[test]
 fn test_tuple_lenses() {
     let v = Vec2(1.0, 2.0);
@@ -28,3 +61,163 @@ fn test_tuple_lenses() {
     assert_eq!(1.0, *Vec20Lens::get(&v));
     assert_eq!(2.0, *Vec21Lens::get(&v));
 }
+
+#[derive(Pickable, Debug, Clone, PartialEq, Eq)]
+pub enum Shape {
+    Square,
+    RoundedRect,
+    #[iroh(label = "Ellipse (experimental)")]
+    Ellipse,
+}
+
+#[test]
+fn test_pickable_variants() {
+    assert_eq!(
+        &[Shape::Square, Shape::RoundedRect, Shape::Ellipse],
+        Shape::VARIANTS
+    );
+}
+
+#[test]
+fn test_pickable_names() {
+    assert_eq!("Square", Shape::Square.name());
+    assert_eq!("Rounded Rect", Shape::RoundedRect.name());
+    assert_eq!("Ellipse (experimental)", Shape::Ellipse.name());
+}
+
+#[derive(Kind, Debug, Clone, Default)]
+#[iroh(key = NoteId)]
+pub struct Note {
+    title: String,
+    #[iroh(label = "Pinned?", field = Checkbox)]
+    pinned: bool,
+    #[iroh(field = Slider)]
+    priority: f32,
+    #[iroh(field = TextArea, lines = 8)]
+    body: String,
+    #[iroh(skip)]
+    revision: u32,
+}
+
+impl NumberRange for NotePriorityLens {
+    const MIN: f32 = 0.0;
+    const MAX: f32 = 10.0;
+    const STEP: f32 = 1.0;
+}
+
+#[derive(Key, Debug, Clone, PartialEq, Eq)]
+pub struct NoteId(u32);
+
+/// Only compiles if `K`'s field is `F`
+fn assert_field<K: Kind<Field = F>, F>() {}
+
+#[test]
+fn test_kind_fields() {
+    assert_field::<
+        Note,
+        ConsFields<
+            TextInputField<"Title", NoteTitleLens>,
+            ConsFields<
+                CheckboxField<"Pinned?", NotePinnedLens>,
+                ConsFields<
+                    SliderField<"Priority", NotePriorityLens>,
+                    TextAreaField<"Body", NoteBodyLens, 8>,
+                >,
+            >,
+        >,
+    >();
+}
+
+#[test]
+fn test_kind_lenses() {
+    let n = Note {
+        revision: 3,
+        ..Note::default()
+    };
+
+    assert_eq!(3, *NoteRevisionLens::get(&n));
+}
+
+#[derive(Key, Debug, Clone, PartialEq, Eq)]
+pub struct NamedId {
+    id: u8,
+}
+
+#[test]
+fn test_key_counts_up() {
+    assert_eq!(NoteId(0), NoteId::first());
+    assert_eq!(NoteId(4), NoteId::next(&NoteId(3)));
+    assert_eq!(NamedId { id: 1 }, NamedId::next(&NamedId::first()));
+}
+
+#[derive(Prism, Variants, Debug, Clone, PartialEq)]
+pub enum Brush {
+    Round {
+        radius: f32,
+    },
+    #[iroh(label = "Rubber stamp")]
+    Stamp(String, f32),
+    Eraser,
+}
+
+#[test]
+fn test_prisms() {
+    let mut b = Brush::Stamp("star".to_string(), 2.0);
+
+    assert_eq!(None, BrushRoundRadiusPrism::get(&b));
+    assert_eq!(Some(&"star".to_string()), BrushStamp0Prism::get(&b));
+
+    *BrushStamp1Prism::get_mut(&mut b).unwrap() = 3.0;
+    assert_eq!(Brush::Stamp("star".to_string(), 3.0), b);
+    assert_eq!(None, BrushRoundRadiusPrism::get_mut(&mut b));
+}
+
+#[derive(Prism, Debug, PartialEq)]
+pub enum Either<'a, L, R: ?Sized = str>
+where
+    L: Clone,
+{
+    Left(L),
+    Right { value: &'a R },
+}
+
+#[test]
+fn test_generic_prisms() {
+    let mut e: Either<u8> = Either::Left(1);
+
+    assert_eq!(Some(&1), EitherLeft0Prism::get(&e));
+    assert_eq!(None, EitherRightValuePrism::get(&e));
+
+    *EitherLeft0Prism::<u8, str>::get_mut(&mut e).unwrap() = 2;
+    assert_eq!(Either::Left(2), e);
+
+    let e: Either<u8> = Either::Right { value: "iroh" };
+    assert_eq!(Some(&"iroh"), EitherRightValuePrism::get(&e));
+}
+
+#[test]
+fn test_variants() {
+    assert_eq!(&["Round", "Rubber stamp", "Eraser"], Brush::NAMES);
+    assert_eq!(1, Brush::Stamp("star".to_string(), 2.0).variant());
+    assert_eq!(2, Brush::Eraser.variant());
+    assert_eq!(Brush::Round { radius: 0.0 }, Brush::default_variant(0));
+    assert_eq!(Brush::Stamp(String::new(), 0.0), Brush::default_variant(1));
+}
+
+#[derive(Variants, Debug, Clone, PartialEq)]
+pub enum Tool<T>
+where
+    T: 'static + Clone + std::fmt::Debug + Send + Default,
+{
+    Pen { width: T },
+    Stamp(String, T),
+    Hand,
+}
+
+#[test]
+fn test_generic_variants() {
+    assert_eq!(&["Pen", "Stamp", "Hand"], Tool::<u8>::NAMES);
+    assert_eq!(1, Tool::Stamp("star".to_string(), 2u8).variant());
+    assert_eq!(Tool::Pen { width: 0u8 }, Tool::default_variant(0));
+    assert_eq!(Tool::<u8>::Hand, Tool::default_variant(2));
+}
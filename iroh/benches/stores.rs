@@ -0,0 +1,97 @@
+//! Compares the object stores on documents of different sizes.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use iroh::{
+    fields::TextInputField,
+    lens::Lens,
+    stores::{BTreeMapContainer, HashMapContainer, VecContainer},
+    Key, Kind, ObjectStore,
+};
+
+#[derive(Debug, Clone, Default)]
+pub struct Record {
+    value: u64,
+}
+
+impl Kind for Record {
+    type Key = RecordId;
+    type Field = TextInputField<"Value", RecordValueLens>;
+}
+
+#[derive(Debug, Clone)]
+pub struct RecordValueLens;
+impl Lens for RecordValueLens {
+    type Source = Record;
+    type Target = u64;
+
+    fn get(source: &Self::Source) -> &Self::Target {
+        &source.value
+    }
+
+    fn get_mut(source: &mut Self::Source) -> &mut Self::Target {
+        &mut source.value
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RecordId(u64);
+impl Key for RecordId {
+    fn first() -> Self {
+        RecordId(0)
+    }
+
+    fn next(last: &Self) -> Self {
+        RecordId(last.0 + 1)
+    }
+}
+
+const SIZES: [u64; 3] = [100, 1_000, 10_000];
+
+/// A store filled with `n` records
+fn filled<C: ObjectStore<Record>>(n: u64) -> C {
+    let mut store = C::empty();
+    for value in 0..n {
+        store.insert_with(Record { value });
+    }
+
+    store
+}
+
+fn bench_get<C: ObjectStore<Record>>(c: &mut Criterion, name: &str) {
+    let mut group = c.benchmark_group(format!("get/{}", name));
+    for &n in SIZES.iter() {
+        let store = filled::<C>(n);
+        group.bench_with_input(BenchmarkId::from_parameter(n), &n, |b, &n| {
+            // Look up every key, in an order that isn't sequential
+            b.iter(|| {
+                for i in 0..n {
+                    black_box(store.get(&RecordId((i * 7919) % n)));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_add<C: ObjectStore<Record>>(c: &mut Criterion, name: &str) {
+    let mut group = c.benchmark_group(format!("add/{}", name));
+    for &n in SIZES.iter() {
+        group.bench_with_input(BenchmarkId::from_parameter(n), &n, |b, &n| {
+            b.iter(|| black_box(filled::<C>(n)))
+        });
+    }
+    group.finish();
+}
+
+fn stores(c: &mut Criterion) {
+    bench_get::<VecContainer<Record>>(c, "vec");
+    bench_get::<HashMapContainer<Record>>(c, "hash_map");
+    bench_get::<BTreeMapContainer<Record>>(c, "btree_map");
+
+    bench_add::<VecContainer<Record>>(c, "vec");
+    bench_add::<HashMapContainer<Record>>(c, "hash_map");
+    bench_add::<BTreeMapContainer<Record>>(c, "btree_map");
+}
+
+criterion_group!(benches, stores);
+criterion_main!(benches);
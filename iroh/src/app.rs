@@ -1,7 +1,19 @@
 //! For when you want to actually run the editor
 
-use crate::{message::Message, pane_zone::PaneZone, theme::Theme, Field, Kind, ObjectStore};
-use iced::{Element, Sandbox};
+use crate::{
+    codec::DocumentCodec,
+    history::History,
+    message::{Message, Task},
+    pane_zone::{PaneLayout, PaneRegistry, PaneZone, Paneable},
+    theme::Theme,
+    toolbar::Toolbar,
+    Field, Kind, ObjectStore,
+};
+use iced::{
+    executor, keyboard, Application, Clipboard, Column, Command, Element, Settings, Subscription,
+};
+use iced_native::{event, subscription, Event};
+use std::path::PathBuf;
 
 /// State of our actual editor.
 pub struct AppState<K: Kind, C: ObjectStore<K>> {
@@ -13,6 +25,9 @@ pub struct AppState<K: Kind, C: ObjectStore<K>> {
 
     /// Appearance settings
     theme: Theme,
+
+    /// The panes that can be opened
+    panes: PaneRegistry<K, C>,
 }
 
 impl<K: Kind, C: ObjectStore<K>> AppState<K, C> {
@@ -21,6 +36,11 @@ impl<K: Kind, C: ObjectStore<K>> AppState<K, C> {
         &self.theme
     }
 
+    /// Get the panes that can be opened.
+    pub fn panes(&self) -> &PaneRegistry<K, C> {
+        &self.panes
+    }
+
     /// Set the new selected object, if it exists. Otherwise, selection is cleared.
     pub fn select(&mut self, selected: Option<K::Key>) {
         self.selected = match selected {
@@ -33,7 +53,13 @@ impl<K: Kind, C: ObjectStore<K>> AppState<K, C> {
     }
 
     /// Get a reference to the currently selected object and its working values
-    pub fn selected(&self) -> Option<(&K::Key, &K, &<<K as Kind>::Field as Field>::WorkingValues)> {
+    pub fn selected(
+        &self,
+    ) -> Option<(
+        &K::Key,
+        &K,
+        &<<K as Kind>::Field as Field<K>>::WorkingValues,
+    )> {
         self.selected
             .as_ref()
             .and_then(|x| self.container.get(x).map(|(v, w)| (x, v, w)))
@@ -42,7 +68,7 @@ impl<K: Kind, C: ObjectStore<K>> AppState<K, C> {
     /// Get a mutable reference to the currently selected object and its working values
     pub fn selected_mut(
         &mut self,
-    ) -> Option<(&mut K, &mut <<K as Kind>::Field as Field>::WorkingValues)> {
+    ) -> Option<(&mut K, &mut <<K as Kind>::Field as Field<K>>::WorkingValues)> {
         match self.selected.as_ref() {
             Some(k) => self.container.get_mut(k),
             None => None,
@@ -76,54 +102,367 @@ impl<K: Kind, C: ObjectStore<K>> AppState<K, C> {
         let k = self.container.add().clone();
         self.select(Some(k));
     }
+
+    /// Remove an object, clearing the selection if it was selected.
+    pub fn delete(&mut self, key: &K::Key) {
+        self.container.remove(key);
+        if self.is_selected(key) {
+            self.selected = None;
+        }
+    }
+
+    /// Copy an object, and select the copy
+    pub fn duplicate(&mut self, key: &K::Key) {
+        let k = self.container.duplicate(key).cloned();
+        if k.is_some() {
+            self.select(k);
+        }
+    }
+
+    /// Swap out the whole object container, such as when opening a new document. Selection is cleared.
+    pub fn replace_container(&mut self, container: C) {
+        self.container = container;
+        self.selected = None;
+    }
 }
 
-/// The main editor window
-pub struct App<K: Kind, C: ObjectStore<K>> {
+/// The main editor window. Use [`AppBuilder`] to configure and run it.
+pub struct App<K: Kind, C: ObjectStore<K>, D: DocumentCodec<K>> {
     /// Stores state for splitting & moving around panes
     pane_zone: PaneZone<K, C>,
 
+    /// Open & save buttons
+    toolbar: Toolbar,
+
     /// Stores our actual application state
     app_state: AppState<K, C>,
+
+    /// Lets us undo & redo mutations
+    history: History<K>,
+
+    /// Used to read & write documents
+    codec: D,
+
+    /// Where the current document was last opened from or saved to
+    path: Option<PathBuf>,
+
+    /// The outcome of the last file operation, shown in the toolbar
+    status: Option<String>,
+
+    /// The name of the application, shown in the window title
+    title: String,
+
+    /// App-wide subscriptions, as well as those from panes
+    subscriptions: Vec<SubscriptionFn<K, C>>,
+}
+
+impl<K: Kind, C: ObjectStore<K>, D: DocumentCodec<K>> App<K, C, D> {
+    /// Open the document at the given path, or ask the user for one.
+    fn open(&mut self, path: Option<PathBuf>) {
+        let path = match path.or_else(|| self.file_dialog().pick_file()) {
+            Some(x) => x,
+            None => return,
+        };
+
+        // Stores that manage their own file get the first go
+        let result = match C::open(&path) {
+            Some(r) => r.map_err(|e| e.to_string()),
+            None => self.codec.read(&path).map_err(|e| e.to_string()),
+        };
+
+        match result {
+            Ok(container) => {
+                self.app_state.replace_container(container);
+                self.history.clear();
+                self.status = Some(format!("Opened {}", path.display()));
+                self.path = Some(path);
+            }
+            Err(e) => {
+                self.status = Some(format!("Couldn't open {}: {}", path.display(), e));
+            }
+        }
+    }
+
+    /// Save the document to the given path, or ask the user for one.
+    fn save(&mut self, path: Option<PathBuf>) {
+        let path = match path.or_else(|| self.file_dialog().save_file()) {
+            Some(x) => x,
+            None => return,
+        };
+
+        let result = match self.app_state.container.save(&path) {
+            Some(r) => r.map_err(|e| e.to_string()),
+            None => self
+                .codec
+                .write(&path, self.app_state.container())
+                .map_err(|e| e.to_string()),
+        };
+
+        match result {
+            Ok(()) => {
+                self.status = Some(format!("Saved {}", path.display()));
+                self.path = Some(path);
+            }
+            Err(e) => {
+                self.status = Some(format!("Couldn't save {}: {}", path.display(), e));
+            }
+        }
+    }
+
+    /// A file dialog filtered to what our codec understands
+    fn file_dialog(&self) -> rfd::FileDialog {
+        let dialog = rfd::FileDialog::new();
+        let extensions = self.codec.extensions();
+
+        if extensions.is_empty() {
+            dialog
+        } else {
+            dialog.add_filter("Document", extensions)
+        }
+    }
 }
 
-impl<K: Kind, C: ObjectStore<K>> Sandbox for App<K, C> {
+impl<K: Kind, C: ObjectStore<K>, D: DocumentCodec<K>> Application for App<K, C, D> {
+    type Executor = executor::Default;
     type Message = Message<K>;
+    type Flags = AppBuilder<K, C, D>;
 
-    fn new() -> Self {
+    fn new(builder: Self::Flags) -> (Self, Command<Self::Message>) {
+        let pane_zone = match builder.layout {
+            Some(layout) => PaneZone::with_layout(layout, &builder.panes),
+            None => PaneZone::new(),
+        };
         let app_state = AppState {
             selected: None,
-            theme: Theme::default(),
-            container: C::empty(),
+            theme: builder.theme,
+            container: builder.container,
+            panes: builder.panes,
         };
-        Self {
-            pane_zone: PaneZone::new(),
+        let mut app = Self {
+            pane_zone,
+            toolbar: Toolbar::new(),
             app_state,
+            history: History::new(),
+            codec: builder.codec,
+            path: None,
+            status: None,
+            title: builder.title,
+            subscriptions: builder.subscriptions,
+        };
+
+        if let Some(path) = builder.path {
+            app.open(Some(path));
         }
+
+        let commands = builder
+            .startup
+            .iter()
+            .map(Task::command)
+            .collect::<Vec<_>>();
+        (app, Command::batch(commands))
     }
 
     fn title(&self) -> String {
-        String::from("A cool application")
+        match &self.path {
+            Some(p) => format!("{} - {}", p.display(), self.title),
+            None => self.title.clone(),
+        }
     }
 
     fn view(&mut self) -> Element<Self::Message> {
-        self.pane_zone.view(&self.app_state)
+        Column::with_children(vec![
+            self.toolbar.view(&self.app_state, self.status.as_deref()),
+            self.pane_zone.view(&self.app_state),
+        ])
+        .into()
     }
 
-    fn update(&mut self, message: Self::Message) {
+    fn update(
+        &mut self,
+        message: Self::Message,
+        _clipboard: &mut Clipboard,
+    ) -> Command<Self::Message> {
         match message {
             Message::Nop => (),
             Message::PaneMessage(msg) => {
-                self.pane_zone.update(msg);
+                self.pane_zone.update(msg, &self.app_state.panes);
             }
             Message::Select(x) => self.app_state.select(Some(x)),
             Message::NewObject => self.app_state.new(),
+            Message::Delete(k) => {
+                self.app_state.delete(&k);
+                self.history.forget(&k);
+            }
+            Message::Duplicate(k) => self.app_state.duplicate(&k),
+            Message::MoveTo(k, i) => {
+                self.app_state.container.move_to(&k, i);
+            }
             Message::Mutate(vm, wm) => {
-                if let Some((v, w)) = self.app_state.selected_mut() {
-                    vm.apply(v);
-                    wm.apply(w);
+                if let Some(k) = self.app_state.selected.clone() {
+                    self.history
+                        .apply(&mut self.app_state.container, &k, vm, wm);
+                }
+            }
+            Message::Undo => {
+                if let Some(k) = self.history.undo(&mut self.app_state.container) {
+                    self.app_state.select(Some(k));
                 }
             }
+            Message::Redo => {
+                if let Some(k) = self.history.redo(&mut self.app_state.container) {
+                    self.app_state.select(Some(k));
+                }
+            }
+            Message::BeginTransaction => self.history.begin(),
+            Message::CommitTransaction => self.history.commit(),
+            Message::Open(path) => self.open(path),
+            Message::Save => self.save(self.path.clone()),
+            Message::SaveAs(path) => self.save(path),
+            Message::Task(task) => return task.command(),
         }
+
+        Command::none()
+    }
+
+    fn subscription(&self) -> Subscription<Self::Message> {
+        let mut subscriptions = vec![
+            subscription::events_with(shortcut),
+            self.pane_zone.subscription(&self.app_state),
+        ];
+        subscriptions.extend(self.subscriptions.iter().map(|f| f(&self.app_state)));
+
+        Subscription::batch(subscriptions)
+    }
+}
+
+/// Gets an app-wide subscription from the current state of the app.
+pub type SubscriptionFn<K, C> = fn(&AppState<K, C>) -> Subscription<Message<K>>;
+
+/// Configures an [`App`] before running it.
+/// Anything that isn't set is left at its default: an empty store, the dark theme, the built in panes, and a single empty pane.
+pub struct AppBuilder<K: Kind, C: ObjectStore<K>, D: DocumentCodec<K>> {
+    title: String,
+    theme: Theme,
+    container: C,
+    path: Option<PathBuf>,
+    codec: D,
+    layout: Option<PaneLayout>,
+    panes: PaneRegistry<K, C>,
+    subscriptions: Vec<SubscriptionFn<K, C>>,
+    startup: Vec<Task<K>>,
+}
+
+impl<K: Kind, C: ObjectStore<K>, D: DocumentCodec<K>> AppBuilder<K, C, D> {
+    /// Create a builder with everything at its default
+    pub fn new() -> Self {
+        Self {
+            title: String::from("A cool application"),
+            theme: Theme::default(),
+            container: C::empty(),
+            path: None,
+            codec: D::default(),
+            layout: None,
+            panes: PaneRegistry::new(),
+            subscriptions: vec![],
+            startup: vec![],
+        }
+    }
+
+    /// Set the application name shown in the window title
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Set the theme to start with
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Start with the given objects, instead of an empty store
+    pub fn store(mut self, container: C) -> Self {
+        self.container = container;
+        self
+    }
+
+    /// Open the document at the given path on startup. This replaces any store given with [`Self::store`].
+    pub fn open(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Use the given codec, instead of its default
+    pub fn codec(mut self, codec: D) -> Self {
+        self.codec = codec;
+        self
+    }
+
+    /// Set how panes are arranged at first, using the names they're registered with
+    pub fn layout(mut self, layout: PaneLayout) -> Self {
+        self.layout = Some(layout);
+        self
+    }
+
+    /// Replace the registry of panes that can be opened
+    pub fn panes(mut self, panes: PaneRegistry<K, C>) -> Self {
+        self.panes = panes;
+        self
+    }
+
+    /// Make a pane available under the given name, as well as those already registered
+    pub fn register<P, F>(mut self, name: &'static str, constructor: F) -> Self
+    where
+        P: 'static + Paneable<K, C>,
+        F: 'static + Fn() -> P,
+    {
+        self.panes.register(name, constructor);
+        self
+    }
+
+    /// Listen for events for as long as the app is running, such as an autosave timer.
+    /// The function is called whenever the app's state changes, so the subscription can depend on it.
+    pub fn subscription(mut self, f: SubscriptionFn<K, C>) -> Self {
+        self.subscriptions.push(f);
+        self
+    }
+
+    /// Run the given task when the app starts, such as loading something in the background
+    pub fn on_startup(mut self, task: Task<K>) -> Self {
+        self.startup.push(task);
+        self
+    }
+
+    /// Run the app with the default window settings.
+    /// To change those, use [`Application::run`] with this builder as the flags.
+    pub fn run(self) -> iced::Result
+    where
+        K: 'static,
+        C: 'static,
+        D: 'static,
+    {
+        App::<K, C, D>::run(Settings::with_flags(self))
+    }
+}
+
+impl<K: Kind, C: ObjectStore<K>, D: DocumentCodec<K>> Default for AppBuilder<K, C, D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Map keyboard shortcuts to the messages they trigger.
+/// Shortcuts work even when a widget has captured the event, so you can undo while typing.
+fn shortcut<K: Kind>(event: Event, _status: event::Status) -> Option<Message<K>> {
+    match event {
+        Event::Keyboard(keyboard::Event::KeyPressed {
+            key_code: keyboard::KeyCode::Z,
+            modifiers,
+        }) if modifiers.is_command_pressed() => Some(if modifiers.shift {
+            Message::Redo
+        } else {
+            Message::Undo
+        }),
+        _ => None,
     }
 }
@@ -0,0 +1,82 @@
+use crate::{
+    codec::{DocumentCodec, Entry},
+    Kind,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::marker::PhantomData;
+
+/// Reads and writes documents as a JSON array of objects, each of which looks like `{"key": ..., "object": ...}`.
+#[derive(Debug, Clone)]
+pub struct JsonCodec<K>(PhantomData<K>);
+
+impl<K> Default for JsonCodec<K> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<K> DocumentCodec<K> for JsonCodec<K>
+where
+    K: Kind + Serialize + DeserializeOwned,
+    K::Key: Serialize + DeserializeOwned,
+{
+    type Error = serde_json::Error;
+
+    fn extensions(&self) -> &[&str] {
+        &["json"]
+    }
+
+    fn encode<'a, I: Iterator<Item = (&'a K::Key, &'a K)>>(
+        &self,
+        objects: I,
+    ) -> Result<Vec<u8>, Self::Error> {
+        serde_json::to_vec_pretty(
+            &objects
+                .map(|(key, object)| Entry { key, object })
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Vec<(K::Key, K)>, Self::Error> {
+        serde_json::from_slice::<Vec<Entry<K::Key, K>>>(bytes)
+            .map(|es| es.into_iter().map(|e| (e.key, e.object)).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Thing;
+
+    #[test]
+    fn round_trip() {
+        let mut b = Thing::new("b \"quoted\"", 2);
+        b.parent = Some(7);
+        let things = vec![(7, Thing::new("a", 1)), (3, b)];
+        let codec = JsonCodec::default();
+
+        let bytes = codec.encode(things.iter().map(|(k, t)| (k, t))).unwrap();
+        assert_eq!(things, codec.decode(&bytes).unwrap());
+    }
+
+    #[test]
+    fn empty() {
+        let codec = JsonCodec::<Thing>::default();
+
+        let bytes = codec.encode(std::iter::empty()).unwrap();
+        assert!(codec.decode(&bytes).unwrap().is_empty());
+    }
+
+    #[test]
+    fn bad_input() {
+        let codec = JsonCodec::<Thing>::default();
+
+        assert!(codec
+            .decode(b"[{\"key\": 0, \"object\": {\"name\": 1}}]")
+            .is_err());
+        assert!(codec
+            .decode(b"[{\"name\": \"no key\", \"size\": 1, \"parent\": null}]")
+            .is_err());
+        assert!(codec.decode(b"[").is_err());
+    }
+}
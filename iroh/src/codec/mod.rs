@@ -0,0 +1,163 @@
+//! Reading and writing documents to and from files.
+//! Codecs for common serde formats are available behind the `json`, `ron` and `toml` features.
+//! The `sqlite` feature has a codec to go with [`crate::stores::SqliteContainer`].
+
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "ron")]
+mod ron;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+#[cfg(feature = "toml")]
+mod toml;
+
+#[cfg(feature = "json")]
+pub use json::JsonCodec;
+#[cfg(feature = "ron")]
+pub use ron::RonCodec;
+#[cfg(feature = "sqlite")]
+pub use sqlite::{ManagedByStore, SqliteCodec};
+#[cfg(feature = "toml")]
+pub use toml::{TomlCodec, TomlError};
+
+use crate::{Kind, ObjectStore};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{Debug, Display},
+    fs, io,
+    path::Path,
+};
+
+/// Converts between the objects in a store and the bytes of a file on disk.
+/// This is how your file format plugs into the editor.
+///
+/// Each object's key is saved along with it, so that references between objects still point to the same place
+/// when the document is opened again.
+pub trait DocumentCodec<K: Kind>: Default {
+    /// The error returned when a document can't be encoded or decoded.
+    type Error: Debug + Display;
+
+    /// File extensions this codec reads and writes, without the leading dot.
+    /// These are used to filter the open & save dialogs.
+    fn extensions(&self) -> &[&str] {
+        &[]
+    }
+
+    /// Encode every object of a document and its key, in order.
+    fn encode<'a, I: Iterator<Item = (&'a K::Key, &'a K)>>(
+        &self,
+        objects: I,
+    ) -> Result<Vec<u8>, Self::Error>;
+
+    /// Decode every object of a document and its key, in order.
+    fn decode(&self, bytes: &[u8]) -> Result<Vec<(K::Key, K)>, Self::Error>;
+
+    /// Read the document at `path` into a new store.
+    fn read<C: ObjectStore<K>>(&self, path: &Path) -> Result<C, DocumentError<Self::Error>> {
+        let bytes = fs::read(path).map_err(DocumentError::Io)?;
+        let objects = self.decode(&bytes).map_err(DocumentError::Codec)?;
+
+        let mut store = C::empty();
+        for (key, object) in objects {
+            if !store.insert_with_key(key.clone(), object) {
+                return Err(DocumentError::DuplicateKey(format!("{:?}", key)));
+            }
+        }
+
+        Ok(store)
+    }
+
+    /// Write every object in `store` to `path`, replacing whatever was there.
+    fn write<C: ObjectStore<K>>(
+        &self,
+        path: &Path,
+        store: &C,
+    ) -> Result<(), DocumentError<Self::Error>> {
+        let bytes = self
+            .encode(store.items().map(|(k, v, _)| (k, v)))
+            .map_err(DocumentError::Codec)?;
+
+        fs::write(path, bytes).map_err(DocumentError::Io)
+    }
+}
+
+/// Something that went wrong while reading or writing a document.
+#[derive(Debug)]
+pub enum DocumentError<E> {
+    /// The file couldn't be read or written.
+    Io(io::Error),
+
+    /// The codec couldn't make sense of the contents.
+    Codec(E),
+
+    /// More than one object in the document has the given key.
+    DuplicateKey(String),
+}
+
+impl<E: Display> Display for DocumentError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DocumentError::Io(e) => write!(f, "{}", e),
+            DocumentError::Codec(e) => write!(f, "{}", e),
+            DocumentError::DuplicateKey(k) => write!(f, "more than one object has the key {}", k),
+        }
+    }
+}
+
+/// An object and its key, as they're written by the serde codecs
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+struct Entry<Key, K> {
+    key: Key,
+    object: K,
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use super::*;
+    use crate::{stores::VecContainer, testing::Thing};
+
+    #[test]
+    fn references_survive_reopening() {
+        let codec = JsonCodec::<Thing>::default();
+        let mut store = VecContainer::empty();
+        let a = *store.insert_with(Thing::new("a", 0));
+        let b = *store.insert_with(Thing::new("b", 0));
+        let c = *store.insert_with(Thing::new("c", 0));
+        store.get_mut(&c).unwrap().0.parent = Some(b);
+
+        // If keys were handed out again from the start, c's parent would point at c
+        store.remove(&a);
+        store.move_to(&c, 0);
+
+        let file = tempfile::NamedTempFile::new().unwrap();
+        codec.write(file.path(), &store).unwrap();
+        let mut reopened: VecContainer<Thing> = codec.read(file.path()).unwrap();
+
+        assert_eq!(reopened.keys().copied().collect::<Vec<_>>(), [c, b]);
+        let parent = reopened.get(&c).unwrap().0.parent.unwrap();
+        assert_eq!(reopened.get(&parent).unwrap().0.name, "b");
+
+        // New objects don't take a key that's in use
+        let d = *reopened.insert_with(Thing::new("d", 0));
+        assert!(d != b && d != c);
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        let codec = JsonCodec::<Thing>::default();
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let things = [(1, Thing::new("a", 0)), (1, Thing::new("b", 0))];
+        std::fs::write(
+            file.path(),
+            codec.encode(things.iter().map(|(k, t)| (k, t))).unwrap(),
+        )
+        .unwrap();
+
+        assert!(matches!(
+            codec.read::<VecContainer<Thing>>(file.path()),
+            Err(DocumentError::DuplicateKey(_))
+        ));
+    }
+}
@@ -0,0 +1,83 @@
+use crate::{
+    codec::{DocumentCodec, Entry},
+    Kind,
+};
+use ron_crate::ser::PrettyConfig;
+use serde::{de::DeserializeOwned, Serialize};
+use std::marker::PhantomData;
+
+/// Reads and writes documents as a RON list of objects, each of which looks like `(key: ..., object: ...)`.
+#[derive(Debug, Clone)]
+pub struct RonCodec<K>(PhantomData<K>);
+
+impl<K> Default for RonCodec<K> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<K> DocumentCodec<K> for RonCodec<K>
+where
+    K: Kind + Serialize + DeserializeOwned,
+    K::Key: Serialize + DeserializeOwned,
+{
+    type Error = ron_crate::Error;
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+
+    fn encode<'a, I: Iterator<Item = (&'a K::Key, &'a K)>>(
+        &self,
+        objects: I,
+    ) -> Result<Vec<u8>, Self::Error> {
+        ron_crate::ser::to_string_pretty(
+            &objects
+                .map(|(key, object)| Entry { key, object })
+                .collect::<Vec<_>>(),
+            PrettyConfig::default(),
+        )
+        .map(String::into_bytes)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Vec<(K::Key, K)>, Self::Error> {
+        ron_crate::de::from_bytes::<Vec<Entry<K::Key, K>>>(bytes)
+            .map(|es| es.into_iter().map(|e| (e.key, e.object)).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Thing;
+
+    #[test]
+    fn round_trip() {
+        let mut b = Thing::new("b \"quoted\"", 2);
+        b.parent = Some(7);
+        let things = vec![(7, Thing::new("a", 1)), (3, b)];
+        let codec = RonCodec::default();
+
+        let bytes = codec.encode(things.iter().map(|(k, t)| (k, t))).unwrap();
+        assert_eq!(things, codec.decode(&bytes).unwrap());
+    }
+
+    #[test]
+    fn empty() {
+        let codec = RonCodec::<Thing>::default();
+
+        let bytes = codec.encode(std::iter::empty()).unwrap();
+        assert!(codec.decode(&bytes).unwrap().is_empty());
+    }
+
+    #[test]
+    fn bad_input() {
+        let codec = RonCodec::<Thing>::default();
+
+        assert!(codec.decode(b"[(key: 0, object: (name: 1))]").is_err());
+        assert!(codec
+            .decode(b"[(name: \"no key\", size: 1, parent: None)]")
+            .is_err());
+        assert!(codec.decode(b"[").is_err());
+    }
+}
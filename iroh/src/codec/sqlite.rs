@@ -0,0 +1,45 @@
+use crate::{codec::DocumentCodec, Kind};
+use std::{fmt::Display, marker::PhantomData};
+
+/// Pairs with [`crate::stores::SqliteContainer`], which reads and writes its own database files.
+/// This only lets the open & save dialogs know about SQLite files; it can't encode or decode anything itself.
+#[derive(Debug, Clone)]
+pub struct SqliteCodec<K>(PhantomData<K>);
+
+impl<K> Default for SqliteCodec<K> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<K: Kind> DocumentCodec<K> for SqliteCodec<K> {
+    type Error = ManagedByStore;
+
+    fn extensions(&self) -> &[&str] {
+        &["sqlite", "db"]
+    }
+
+    fn encode<'a, I: Iterator<Item = (&'a K::Key, &'a K)>>(
+        &self,
+        _objects: I,
+    ) -> Result<Vec<u8>, Self::Error> {
+        Err(ManagedByStore)
+    }
+
+    fn decode(&self, _bytes: &[u8]) -> Result<Vec<(K::Key, K)>, Self::Error> {
+        Err(ManagedByStore)
+    }
+}
+
+/// Returned when [`SqliteCodec`] is used with a store other than [`crate::stores::SqliteContainer`].
+#[derive(Debug, Clone, Copy)]
+pub struct ManagedByStore;
+
+impl Display for ManagedByStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "SQLite documents can only be used with a SqliteContainer"
+        )
+    }
+}
@@ -0,0 +1,116 @@
+use crate::{
+    codec::{DocumentCodec, Entry},
+    Kind,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{fmt::Display, marker::PhantomData};
+
+/// Reads and writes documents as TOML.
+/// A TOML document has to be a table, so objects are stored as an array of tables called `objects`,
+/// each with a `key` and an `object` table.
+#[derive(Debug, Clone)]
+pub struct TomlCodec<K>(PhantomData<K>);
+
+impl<K> Default for TomlCodec<K> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+/// The root table we write
+#[derive(Serialize)]
+struct Document<'a, Key, K> {
+    objects: Vec<Entry<&'a Key, &'a K>>,
+}
+
+/// The root table we read
+#[derive(Deserialize)]
+struct OwnedDocument<Key, K> {
+    // A plain `default` would make serde require `Key: Default`
+    #[serde(default = "Vec::new")]
+    objects: Vec<Entry<Key, K>>,
+}
+
+impl<K> DocumentCodec<K> for TomlCodec<K>
+where
+    K: Kind + Serialize + DeserializeOwned,
+    K::Key: Serialize + DeserializeOwned,
+{
+    type Error = TomlError;
+
+    fn extensions(&self) -> &[&str] {
+        &["toml"]
+    }
+
+    fn encode<'a, I: Iterator<Item = (&'a K::Key, &'a K)>>(
+        &self,
+        objects: I,
+    ) -> Result<Vec<u8>, Self::Error> {
+        toml_crate::to_string_pretty(&Document {
+            objects: objects.map(|(key, object)| Entry { key, object }).collect(),
+        })
+        .map(String::into_bytes)
+        .map_err(TomlError::Serialize)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Vec<(K::Key, K)>, Self::Error> {
+        toml_crate::from_slice::<OwnedDocument<K::Key, K>>(bytes)
+            .map(|d| d.objects.into_iter().map(|e| (e.key, e.object)).collect())
+            .map_err(TomlError::Deserialize)
+    }
+}
+
+/// TOML uses different errors for reading and writing.
+#[derive(Debug)]
+pub enum TomlError {
+    Serialize(toml_crate::ser::Error),
+    Deserialize(toml_crate::de::Error),
+}
+
+impl Display for TomlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TomlError::Serialize(e) => write!(f, "{}", e),
+            TomlError::Deserialize(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Thing;
+
+    #[test]
+    fn round_trip() {
+        let mut b = Thing::new("b \"quoted\"", 2);
+        b.parent = Some(7);
+        let things = vec![(7, Thing::new("a", 1)), (3, b)];
+        let codec = TomlCodec::default();
+
+        let bytes = codec.encode(things.iter().map(|(k, t)| (k, t))).unwrap();
+        assert_eq!(things, codec.decode(&bytes).unwrap());
+    }
+
+    #[test]
+    fn empty() {
+        let codec = TomlCodec::<Thing>::default();
+
+        let bytes = codec.encode(std::iter::empty()).unwrap();
+        assert!(codec.decode(&bytes).unwrap().is_empty());
+    }
+
+    #[test]
+    fn bad_input() {
+        let codec = TomlCodec::<Thing>::default();
+
+        assert!(matches!(
+            codec.decode(b"[[objects]]\nkey = 0\n[objects.object]\nname = 1"),
+            Err(TomlError::Deserialize(_))
+        ));
+        assert!(codec.decode(b"objects = [").is_err());
+        assert!(codec
+            .decode(b"[[objects]]\nname = \"no key\"\nsize = 1")
+            .is_err());
+    }
+}
@@ -0,0 +1,50 @@
+use crate::{
+    app::AppState,
+    lens::Lens,
+    message::Message,
+    mutation::{LensSet, NopMutator},
+    Field, Kind, ObjectStore,
+};
+use iced::{Align, Checkbox, Element, Row, Text};
+use std::{fmt::Debug, marker::PhantomData};
+
+/// A field for booleans, which the user toggles with a checkbox.
+/// Uses a lens to get/set the right field on the struct.
+#[derive(Debug, Clone)]
+pub struct CheckboxField<const N: &'static str, L>(PhantomData<L>);
+
+impl<K: Kind, L: 'static + Lens<Target = bool>, const N: &'static str> Field<K>
+    for CheckboxField<N, L>
+where
+    L::Source: 'static + Debug + Clone + Send,
+{
+    type Source = L::Source;
+    type WorkingValues = ();
+
+    fn view<C: ObjectStore<K>>(
+        &mut self,
+        _key: &K::Key,
+        val: &L::Source,
+        app_state: &AppState<K, C>,
+        _working: &(),
+    ) -> Vec<Element<Message<K, Self::WorkingValues, L::Source>>> {
+        vec![Row::with_children(vec![
+            Text::new(N).color(app_state.theme().text_primary()).into(),
+            Checkbox::new(*L::get(val), "", |checked| {
+                Message::Mutate(Box::new(LensSet::<L>::new(checked)), Box::new(NopMutator))
+            })
+            .style(app_state.theme().checkbox())
+            .into(),
+        ])
+        .spacing(4)
+        .padding(8)
+        .align_items(Align::Center)
+        .into()]
+    }
+}
+
+impl<const N: &'static str, L> Default for CheckboxField<N, L> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
@@ -0,0 +1,425 @@
+use crate::{
+    app::AppState,
+    fields::{
+        drag::{self, DragWatch},
+        focus::{self, FocusWatch},
+        toggle::Toggle,
+    },
+    lens::{Lens, RootLens},
+    message::Message,
+    mutation::{LensSet, NopMutator, Session},
+    Field, Kind, ObjectStore,
+};
+use iced::{
+    slider, text_input, Align, Color, Column, Container, Element, Length, Row, Slider, Space, Text,
+    TextInput,
+};
+use std::{fmt::Debug, marker::PhantomData};
+
+/// A colour type which can be edited with a [`ColorField`].
+/// Implement this to convert your own colour structs. Every component is between 0 and 1.
+pub trait Rgba: Sized {
+    fn to_rgba(&self) -> [f32; 4];
+    fn from_rgba(rgba: [f32; 4]) -> Self;
+}
+
+impl Rgba for Color {
+    fn to_rgba(&self) -> [f32; 4] {
+        [self.r, self.g, self.b, self.a]
+    }
+
+    fn from_rgba(rgba: [f32; 4]) -> Self {
+        Color::from(rgba)
+    }
+}
+
+impl Rgba for [f32; 4] {
+    fn to_rgba(&self) -> [f32; 4] {
+        *self
+    }
+
+    fn from_rgba(rgba: [f32; 4]) -> Self {
+        rgba
+    }
+}
+
+/// Components from 0 to 255
+impl Rgba for [u8; 4] {
+    fn to_rgba(&self) -> [f32; 4] {
+        let mut rgba = [0.0; 4];
+        for (c, x) in rgba.iter_mut().zip(self.iter()) {
+            *c = *x as f32 / 255.0;
+        }
+
+        rgba
+    }
+
+    fn from_rgba(rgba: [f32; 4]) -> Self {
+        let mut out = [0; 4];
+        for (x, c) in out.iter_mut().zip(rgba.iter()) {
+            *x = (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+        }
+
+        out
+    }
+}
+
+/// A field for colours, showing a swatch, a hex code, and sliders for each component.
+/// The sliders can be switched between RGB and HSV by clicking the label under the swatch.
+/// Each drag of a slider, and everything typed while the hex input has focus, is undone as one step.
+#[derive(Debug, Clone)]
+pub struct ColorField<const N: &'static str, L> {
+    hex_value: String,
+    hex_state: text_input::State,
+    hex_focus_state: focus::State,
+    hex_session: Session,
+    was_focused: bool,
+    slider_states: [slider::State; 4],
+    slider_drag_state: drag::State,
+    slider_session: Session,
+    hsv: bool,
+    _d: PhantomData<L>,
+}
+
+/// The working values for a [`ColorField`]
+#[derive(Debug, Clone, Default)]
+pub struct ColorWorkingValues {
+    /// What's in the hex input, if it isn't a valid colour
+    hex: Option<String>,
+
+    /// The last hue set, used when the colour is grey and so has no hue of its own
+    hue: Option<f32>,
+}
+
+impl<K: Kind, L: 'static + Lens, const N: &'static str> Field<K> for ColorField<N, L>
+where
+    L::Source: 'static + Debug + Clone + Send,
+    L::Target: 'static + Rgba + Debug + Clone + Send,
+{
+    type Source = L::Source;
+    type WorkingValues = ColorWorkingValues;
+
+    fn view<C: ObjectStore<K>>(
+        &mut self,
+        _key: &K::Key,
+        val: &L::Source,
+        app_state: &AppState<K, C>,
+        working: &ColorWorkingValues,
+    ) -> Vec<Element<Message<K, Self::WorkingValues, L::Source>>> {
+        let theme = app_state.theme();
+        let rgba = L::get(val).to_rgba();
+        let (mut h, s, v) = rgb_to_hsv(rgba);
+        if s == 0.0 || v == 0.0 {
+            h = working.hue.unwrap_or(h);
+        }
+
+        self.hex_value = working.hex.clone().unwrap_or_else(|| to_hex(rgba));
+
+        // Start a new undo step each time the input is focused.
+        // Clicking it produces a message, so this happens before the first keystroke.
+        let focused = self.hex_state.is_focused();
+        if focused && !self.was_focused {
+            self.hex_session = Session::new();
+        }
+        self.was_focused = focused;
+
+        let hex_session = self.hex_session;
+        let hue = working.hue;
+        let header = Row::with_children(vec![
+            Text::new(N).color(theme.text_primary()).into(),
+            Container::new(Space::new(Length::Units(24), Length::Units(24)))
+                .style(theme.swatch(Color::from(rgba)))
+                .into(),
+            FocusWatch::new(
+                &mut self.hex_focus_state,
+                TextInput::new(
+                    &mut self.hex_state,
+                    "#rrggbbaa",
+                    &self.hex_value,
+                    move |new| match from_hex(&new) {
+                        Some(rgba) => Message::Mutate(
+                            Box::new(LensSet::<L>::in_session(
+                                L::Target::from_rgba(rgba),
+                                hex_session,
+                            )),
+                            Box::new(LensSet::<RootLens<_>>::in_session(
+                                ColorWorkingValues::default(),
+                                hex_session,
+                            )),
+                        ),
+                        None => Message::Mutate(
+                            Box::new(NopMutator),
+                            Box::new(LensSet::<RootLens<_>>::in_session(
+                                ColorWorkingValues {
+                                    hex: Some(new),
+                                    hue,
+                                },
+                                hex_session,
+                            )),
+                        ),
+                    },
+                )
+                .on_submit(Message::CommitTransaction)
+                .style(theme.text_input())
+                .padding(5),
+                Message::Nop,
+                Message::CommitTransaction,
+            )
+            .into(),
+        ])
+        .spacing(4)
+        .padding(8)
+        .align_items(Align::Center);
+
+        let hsv = self.hsv;
+        let mode = Text::new(if hsv { "HSV" } else { "RGB" }).color(theme.text_accent());
+        let col = Column::new()
+            .push(header)
+            .push(
+                Row::new()
+                    .padding(8)
+                    .push(Toggle::new(&mut self.hsv, mode, Message::Nop)),
+            );
+
+        let (labels, values, maxes) = if hsv {
+            (
+                ["H", "S", "V", "A"],
+                [h, s * 100.0, v * 100.0, rgba[3] * 255.0],
+                [360.0, 100.0, 100.0, 255.0],
+            )
+        } else {
+            (
+                ["R", "G", "B", "A"],
+                [
+                    rgba[0] * 255.0,
+                    rgba[1] * 255.0,
+                    rgba[2] * 255.0,
+                    rgba[3] * 255.0,
+                ],
+                [255.0; 4],
+            )
+        };
+
+        // Start a new undo step between drags, ready for the first message of the next one
+        if !self.slider_drag_state.is_dragging() {
+            self.slider_session = Session::new();
+        }
+        let slider_session = self.slider_session;
+        let mut sliders = Column::new().spacing(4).padding(8);
+        for (i, state) in self.slider_states.iter_mut().enumerate() {
+            sliders = sliders.push(
+                Row::with_children(vec![
+                    Text::new(labels[i])
+                        .color(theme.text_primary())
+                        .width(Length::Units(16))
+                        .into(),
+                    Slider::new(state, 0.0..=maxes[i], values[i], move |x| {
+                        let mut values = values;
+                        values[i] = x;
+
+                        let (rgba, hue) = if hsv {
+                            let [r, g, b] =
+                                hsv_to_rgb(values[0], values[1] / 100.0, values[2] / 100.0);
+                            ([r, g, b, values[3] / 255.0], Some(values[0]))
+                        } else {
+                            let mut rgba = values;
+                            for c in rgba.iter_mut() {
+                                *c /= 255.0;
+                            }
+                            (rgba, hue)
+                        };
+
+                        Message::Mutate(
+                            Box::new(LensSet::<L>::in_session(
+                                L::Target::from_rgba(rgba),
+                                slider_session,
+                            )),
+                            Box::new(LensSet::<RootLens<_>>::in_session(
+                                ColorWorkingValues { hex: None, hue },
+                                slider_session,
+                            )),
+                        )
+                    })
+                    .step(1.0)
+                    .on_release(Message::CommitTransaction)
+                    .style(theme.slider())
+                    .width(Length::Fill)
+                    .into(),
+                    Text::new(format!("{:.0}", values[i]))
+                        .color(theme.text_primary())
+                        .width(Length::Units(32))
+                        .into(),
+                ])
+                .spacing(4)
+                .align_items(Align::Center),
+            );
+        }
+
+        vec![col
+            .push(DragWatch::new(&mut self.slider_drag_state, sliders))
+            .into()]
+    }
+}
+
+impl<const N: &'static str, L> Default for ColorField<N, L> {
+    fn default() -> Self {
+        Self {
+            hex_value: "".to_string(),
+            hex_state: text_input::State::default(),
+            hex_focus_state: focus::State::default(),
+            hex_session: Session::new(),
+            was_focused: false,
+            slider_states: Default::default(),
+            slider_drag_state: drag::State::default(),
+            slider_session: Session::new(),
+            hsv: false,
+            _d: PhantomData,
+        }
+    }
+}
+
+/// Format a colour as `#rrggbb`, or `#rrggbbaa` if it's not opaque.
+fn to_hex(rgba: [f32; 4]) -> String {
+    let [r, g, b, a] = <[u8; 4]>::from_rgba(rgba);
+    if a == 255 {
+        format!("#{:02x}{:02x}{:02x}", r, g, b)
+    } else {
+        format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+    }
+}
+
+/// Parse a colour written as `rrggbb` or `rrggbbaa`, with or without a leading `#`.
+fn from_hex(s: &str) -> Option<[f32; 4]> {
+    let s = s.trim();
+    let s = s.strip_prefix('#').unwrap_or(s);
+    if !(s.len() == 6 || s.len() == 8) || !s.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let mut out = [255; 4];
+    for (i, x) in out.iter_mut().enumerate().take(s.len() / 2) {
+        *x = u8::from_str_radix(&s[i * 2..i * 2 + 2], 16).ok()?;
+    }
+
+    Some(out.to_rgba())
+}
+
+/// Convert RGB to hue (in degrees), saturation and value. Alpha is ignored.
+fn rgb_to_hsv([r, g, b, _]: [f32; 4]) -> (f32, f32, f32) {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let d = max - min;
+
+    let h = if d == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / d).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / d + 2.0)
+    } else {
+        60.0 * ((r - g) / d + 4.0)
+    };
+    let s = if max > 0.0 { d / max } else { 0.0 };
+
+    (h, s, max)
+}
+
+/// Convert hue (in degrees), saturation and value to RGB.
+fn hsv_to_rgb(h: f32, s: f32, v: f32) -> [f32; 3] {
+    let c = v * s;
+    let h = (h / 60.0).rem_euclid(6.0);
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let m = v - c;
+
+    let [r, g, b] = match h as u32 {
+        0 => [c, x, 0.0],
+        1 => [x, c, 0.0],
+        2 => [0.0, c, x],
+        3 => [0.0, x, c],
+        4 => [x, 0.0, c],
+        _ => [c, 0.0, x],
+    };
+
+    [r + m, g + m, b + m]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    #[test]
+    fn hex_round_trip() {
+        for hex in ["#000000", "#ffffff", "#12abef", "#12abef80"].iter() {
+            assert_eq!(to_hex(from_hex(hex).unwrap()), *hex);
+        }
+    }
+
+    #[test]
+    fn to_hex_opaque() {
+        assert_eq!(to_hex([1.0, 0.0, 0.0, 1.0]), "#ff0000");
+        assert_eq!(to_hex([1.0, 0.0, 0.0, 0.0]), "#ff000000");
+    }
+
+    #[test]
+    fn from_hex_formats() {
+        assert_eq!(from_hex("ff0000"), Some([1.0, 0.0, 0.0, 1.0]));
+        assert_eq!(from_hex(" #FF0000 "), Some([1.0, 0.0, 0.0, 1.0]));
+        assert_eq!(from_hex("#ff000000"), Some([1.0, 0.0, 0.0, 0.0]));
+    }
+
+    #[test]
+    fn from_hex_bad_input() {
+        for bad in [
+            "",
+            "#",
+            "#fff",
+            "zzzzzz",
+            "#ff00zz",
+            "#ff00000",
+            "##ff0000",
+            "#ff0000ff00",
+        ]
+        .iter()
+        {
+            assert_eq!(from_hex(bad), None, "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn hsv_known() {
+        assert_eq!(rgb_to_hsv([1.0, 0.0, 0.0, 1.0]), (0.0, 1.0, 1.0));
+        assert_eq!(rgb_to_hsv([0.0, 1.0, 0.0, 1.0]), (120.0, 1.0, 1.0));
+        assert_eq!(rgb_to_hsv([0.0, 0.0, 1.0, 1.0]), (240.0, 1.0, 1.0));
+        assert_eq!(rgb_to_hsv([0.5, 0.5, 0.5, 1.0]), (0.0, 0.0, 0.5));
+        assert_eq!(rgb_to_hsv([0.0, 0.0, 0.0, 1.0]), (0.0, 0.0, 0.0));
+
+        assert_eq!(hsv_to_rgb(0.0, 1.0, 1.0), [1.0, 0.0, 0.0]);
+        assert_eq!(hsv_to_rgb(360.0, 1.0, 1.0), [1.0, 0.0, 0.0]);
+        assert_eq!(hsv_to_rgb(-120.0, 1.0, 1.0), [0.0, 0.0, 1.0]);
+        assert_eq!(hsv_to_rgb(200.0, 0.0, 0.25), [0.25, 0.25, 0.25]);
+    }
+
+    #[test]
+    fn hsv_round_trip() {
+        for r in 0..=4 {
+            for g in 0..=4 {
+                for b in 0..=4 {
+                    let rgb = [r as f32 / 4.0, g as f32 / 4.0, b as f32 / 4.0];
+                    let (h, s, v) = rgb_to_hsv([rgb[0], rgb[1], rgb[2], 1.0]);
+                    let back = hsv_to_rgb(h, s, v);
+                    assert!(
+                        rgb.iter().zip(back.iter()).all(|(a, b)| close(*a, *b)),
+                        "{:?} -> {:?} -> {:?}",
+                        rgb,
+                        (h, s, v),
+                        back
+                    );
+                }
+            }
+        }
+    }
+}
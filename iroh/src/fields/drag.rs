@@ -0,0 +1,80 @@
+//! A widget which keeps track of whether the widget inside it is being dragged.
+
+use iced_native::{event, mouse, Clipboard, Element, Event, Layout, Point, Widget};
+
+/// Whether the content of a [`DragWatch`] is being dragged
+#[derive(Debug, Clone, Copy, Default)]
+pub struct State {
+    dragging: bool,
+}
+
+impl State {
+    /// Whether the mouse was pressed on the content and hasn't been released yet
+    pub fn is_dragging(&self) -> bool {
+        self.dragging
+    }
+}
+
+/// Wraps a widget that's dragged, such as a slider.
+/// Those don't say when a drag starts, so a field can't tell one drag from the next.
+/// This records a drag from when the content is pressed until the mouse is released anywhere.
+pub struct DragWatch<'a, Message, Renderer> {
+    state: &'a mut State,
+    content: Element<'a, Message, Renderer>,
+}
+
+impl<'a, Message, Renderer> DragWatch<'a, Message, Renderer> {
+    /// Watch `content`, recording drags in `state`
+    pub fn new(state: &'a mut State, content: impl Into<Element<'a, Message, Renderer>>) -> Self {
+        Self {
+            state,
+            content: content.into(),
+        }
+    }
+}
+
+impl<'a, Message, Renderer: iced_native::Renderer> Widget<Message, Renderer>
+    for DragWatch<'a, Message, Renderer>
+{
+    wrap_content!();
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        messages: &mut Vec<Message>,
+    ) -> event::Status {
+        match event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                self.state.dragging = layout.bounds().contains(cursor_position);
+            }
+            Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                self.state.dragging = false;
+            }
+            _ => {}
+        }
+
+        self.content.on_event(
+            event,
+            layout,
+            cursor_position,
+            renderer,
+            clipboard,
+            messages,
+        )
+    }
+}
+
+impl<'a, Message, Renderer> From<DragWatch<'a, Message, Renderer>>
+    for Element<'a, Message, Renderer>
+where
+    Message: 'a,
+    Renderer: 'a + iced_native::Renderer,
+{
+    fn from(watch: DragWatch<'a, Message, Renderer>) -> Self {
+        Element::new(watch)
+    }
+}
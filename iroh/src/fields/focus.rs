@@ -0,0 +1,85 @@
+//! A widget which reports when the widget inside it gains or loses focus.
+
+use iced_native::{event, mouse, Clipboard, Element, Event, Layout, Point, Widget};
+
+/// Whether the content of a [`FocusWatch`] was last clicked on
+#[derive(Debug, Clone, Copy, Default)]
+pub struct State {
+    focused: bool,
+}
+
+/// Wraps a widget that's focused by clicking it, such as a text input.
+/// Those don't produce a message when they gain or lose focus, so the view isn't rebuilt until something is typed.
+/// This produces `on_focus` when the content is clicked, and `on_blur` when something else is.
+pub struct FocusWatch<'a, Message, Renderer> {
+    state: &'a mut State,
+    content: Element<'a, Message, Renderer>,
+    on_focus: Message,
+    on_blur: Message,
+}
+
+impl<'a, Message, Renderer> FocusWatch<'a, Message, Renderer> {
+    /// Watch `content`, producing `on_focus` and `on_blur` as it's clicked on and off
+    pub fn new(
+        state: &'a mut State,
+        content: impl Into<Element<'a, Message, Renderer>>,
+        on_focus: Message,
+        on_blur: Message,
+    ) -> Self {
+        Self {
+            state,
+            content: content.into(),
+            on_focus,
+            on_blur,
+        }
+    }
+}
+
+impl<'a, Message: Clone, Renderer: iced_native::Renderer> Widget<Message, Renderer>
+    for FocusWatch<'a, Message, Renderer>
+{
+    wrap_content!();
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        messages: &mut Vec<Message>,
+    ) -> event::Status {
+        if let Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) = event {
+            let inside = layout.bounds().contains(cursor_position);
+            if inside != self.state.focused {
+                self.state.focused = inside;
+                messages.push(if inside {
+                    self.on_focus.clone()
+                } else {
+                    self.on_blur.clone()
+                });
+            }
+        }
+
+        // The content still gets every event, so it can focus itself
+        self.content.on_event(
+            event,
+            layout,
+            cursor_position,
+            renderer,
+            clipboard,
+            messages,
+        )
+    }
+}
+
+impl<'a, Message, Renderer> From<FocusWatch<'a, Message, Renderer>>
+    for Element<'a, Message, Renderer>
+where
+    Message: 'a + Clone,
+    Renderer: 'a + iced_native::Renderer,
+{
+    fn from(watch: FocusWatch<'a, Message, Renderer>) -> Self {
+        Element::new(watch)
+    }
+}
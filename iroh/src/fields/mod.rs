@@ -1,3 +1,102 @@
+/// Implements the parts of [`iced_native::Widget`] for a widget that wraps some `content`,
+/// so that it's laid out and drawn exactly like its content, and only handles events differently.
+/// Each wrapper still hashes its own type, so replacing one with another redoes the layout.
+macro_rules! wrap_content {
+    () => {
+        fn width(&self) -> iced_native::Length {
+            self.content.width()
+        }
+
+        fn height(&self) -> iced_native::Length {
+            self.content.height()
+        }
+
+        fn layout(
+            &self,
+            renderer: &Renderer,
+            limits: &iced_native::layout::Limits,
+        ) -> iced_native::layout::Node {
+            self.content.layout(renderer, limits)
+        }
+
+        fn draw(
+            &self,
+            renderer: &mut Renderer,
+            defaults: &Renderer::Defaults,
+            layout: iced_native::Layout<'_>,
+            cursor_position: iced_native::Point,
+            viewport: &iced_native::Rectangle,
+        ) -> Renderer::Output {
+            self.content
+                .draw(renderer, defaults, layout, cursor_position, viewport)
+        }
+
+        fn hash_layout(&self, state: &mut iced_native::Hasher) {
+            struct Marker;
+            std::hash::Hash::hash(&std::any::TypeId::of::<Marker>(), state);
+
+            self.content.hash_layout(state);
+        }
+    };
+}
+
+pub mod checkbox;
+pub mod color;
+mod drag;
+mod focus;
+mod multiline;
+pub mod nested;
+pub mod number;
+pub mod option;
+pub mod pick_list;
+pub mod prism;
+pub mod reference;
+mod scrub;
+pub mod slider;
+pub mod spinner;
+pub mod text_area;
 pub mod text_input;
+mod toggle;
+pub mod variant;
+pub mod vec;
 
+pub use checkbox::CheckboxField;
+pub use color::{ColorField, Rgba};
+pub use nested::NestedField;
+pub use number::{Number, NumberRange};
+pub use option::OptionField;
+pub use pick_list::{PickListField, Pickable};
+pub use prism::PrismField;
+pub use reference::{Reference, ReferenceField};
+pub use slider::SliderField;
+pub use spinner::NumberSpinnerField;
+pub use text_area::TextAreaField;
 pub use text_input::TextInputField;
+pub use variant::{EnumField, Variants};
+pub use vec::VecField;
+
+use crate::{lens::Lens, message::Message, mutation::InnerMutation, Kind};
+use iced::{Element, Length, Row, Space};
+use std::fmt::Debug;
+
+/// How far the fields inside another field, such as an [`OptionField`] or [`VecField`], are indented
+const INDENT: u16 = 16;
+
+/// Put some inner fields in a row after a gap, so they're indented under the field that holds them
+fn indented<'a, M: 'a>(inner: impl Into<Element<'a, M>>) -> Row<'a, M> {
+    Row::new()
+        .push(Space::with_width(Length::Units(INDENT)))
+        .push(inner)
+}
+
+/// Lift a message from the field for `L::Target` to one for `L::Source`
+fn lift<K, L, W>(m: Message<K, W, L::Target>) -> Message<K, W, L::Source>
+where
+    K: Kind,
+    L: 'static + Lens,
+    L::Source: 'static,
+    L::Target: 'static + Debug + Clone + Send,
+    W: 'static + Debug + Clone + Send,
+{
+    m.map_value(|v| Box::new(InnerMutation::<L>::new(v)))
+}
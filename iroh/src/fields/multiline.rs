@@ -0,0 +1,601 @@
+//! A widget which edits text that can span multiple lines.
+
+use iced_native::{
+    event, keyboard, layout, mouse, text, Clipboard, Element, Event, Hasher, HorizontalAlignment,
+    Layout, Length, Point, Rectangle, Size, Vector, VerticalAlignment, Widget,
+};
+use std::{hash::Hash, ops::Range};
+
+/// Whether a [`MultilineInput`] has focus, where its cursor is, and how far it's scrolled
+#[derive(Debug, Clone, Copy, Default)]
+pub struct State {
+    focused: bool,
+
+    /// In bytes, always on a character boundary
+    cursor: usize,
+
+    /// How far down the text is scrolled, in pixels
+    scroll: f32,
+}
+
+impl State {
+    /// Whether the input has focus
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+}
+
+/// Edits some text, wrapping long lines and scrolling if there's more than fits.
+/// While it has focus, `caret` is drawn where the cursor is, and clicking on the text moves the cursor there.
+pub struct MultilineInput<'a, Message, Renderer> {
+    state: &'a mut State,
+    value: String,
+    size: u16,
+    padding: u16,
+    caret: Element<'a, Message, Renderer>,
+    on_change: Box<dyn Fn(String) -> Message>,
+    on_move: Message,
+    on_blur: Option<Message>,
+}
+
+impl<'a, Message: Clone, Renderer: text::Renderer> MultilineInput<'a, Message, Renderer> {
+    /// Create an input for the given text, with lines `size` pixels tall.
+    /// `on_change` is called with the new text when it's edited, and `on_move` is produced when only the cursor moves.
+    pub fn new<F>(
+        state: &'a mut State,
+        value: &str,
+        size: u16,
+        caret: impl Into<Element<'a, Message, Renderer>>,
+        on_change: F,
+        on_move: Message,
+    ) -> Self
+    where
+        F: 'static + Fn(String) -> Message,
+    {
+        Self {
+            state,
+            value: value.to_string(),
+            size,
+            padding: 0,
+            caret: caret.into(),
+            on_change: Box::new(on_change),
+            on_move,
+            on_blur: None,
+        }
+    }
+
+    /// Set the space between the text and the edge of the input
+    pub fn padding(mut self, padding: u16) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Set the message to produce when the input loses focus
+    pub fn on_blur(mut self, message: Message) -> Self {
+        self.on_blur = Some(message);
+        self
+    }
+
+    /// Put `s` at the cursor, and move the cursor to after it
+    fn insert(&mut self, cursor: usize, s: &str, messages: &mut Vec<Message>) {
+        self.value.insert_str(cursor, s);
+        self.state.cursor = cursor + s.len();
+        messages.push((self.on_change)(self.value.clone()));
+    }
+
+    /// Remove the text between two cursor positions, and move the cursor to where it was
+    fn remove(&mut self, start: usize, end: usize, messages: &mut Vec<Message>) {
+        self.value.replace_range(start..end, "");
+        self.state.cursor = start;
+        messages.push((self.on_change)(self.value.clone()));
+    }
+
+    /// Lose focus, if the input has it
+    fn blur(&mut self, messages: &mut Vec<Message>) {
+        if self.state.focused {
+            self.state.focused = false;
+            messages.push(self.on_blur.clone().unwrap_or_else(|| self.on_move.clone()));
+        }
+    }
+
+    /// Where the text goes, inside the padding
+    fn text_bounds(&self, layout: Layout<'_>) -> Rectangle {
+        let bounds = layout.bounds();
+        let padding = f32::from(self.padding);
+
+        Rectangle {
+            x: bounds.x + padding,
+            y: bounds.y + padding,
+            width: (bounds.width - 2.0 * padding).max(0.0),
+            height: (bounds.height - 2.0 * padding).max(0.0),
+        }
+    }
+
+    /// The width of some text, as it's drawn
+    fn measure(&self, renderer: &Renderer, s: &str) -> f32 {
+        renderer
+            .measure(s, self.size, Renderer::Font::default(), Size::INFINITY)
+            .0
+    }
+
+    /// The text split into the lines it's shown as
+    fn lines(&self, renderer: &Renderer, width: f32) -> Vec<Range<usize>> {
+        wrap(&self.value, width, |s| self.measure(renderer, s))
+    }
+
+    /// Scroll by `delta` pixels, no further than the start or end of the text
+    fn scroll(&mut self, delta: f32, lines: usize, bounds: Rectangle) {
+        let end = (lines as f32 * f32::from(self.size) - bounds.height).max(0.0);
+        self.state.scroll = (self.state.scroll + delta).max(0.0).min(end);
+    }
+
+    /// Scroll just far enough that the line with the cursor on it can be seen
+    fn scroll_to_cursor(&mut self, renderer: &Renderer, bounds: Rectangle) {
+        let lines = self.lines(renderer, bounds.width);
+        let height = f32::from(self.size);
+        let cursor = clamp_cursor(&self.value, self.state.cursor);
+        let top = line_of(&lines, cursor) as f32 * height;
+
+        let scroll = self.state.scroll;
+        let wanted = scroll.min(top).max(top + height - bounds.height);
+        self.scroll(wanted - scroll, lines.len(), bounds);
+    }
+}
+
+impl<'a, Message: Clone, Renderer: text::Renderer> Widget<Message, Renderer>
+    for MultilineInput<'a, Message, Renderer>
+{
+    fn width(&self) -> Length {
+        Length::Fill
+    }
+
+    fn height(&self) -> Length {
+        Length::Fill
+    }
+
+    fn layout(&self, _renderer: &Renderer, limits: &layout::Limits) -> layout::Node {
+        layout::Node::new(
+            limits
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .resolve(Size::ZERO),
+        )
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut Renderer,
+        defaults: &Renderer::Defaults,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        viewport: &Rectangle,
+    ) -> Renderer::Output {
+        let bounds = layout.bounds();
+        let text_bounds = self.text_bounds(layout);
+        let height = f32::from(self.size);
+        let lines = self.lines(renderer, text_bounds.width);
+
+        // Only the lines that can be seen are drawn, clipped to the text bounds
+        let draw_line = |renderer: &mut Renderer, i: usize, content: &str| {
+            text::Renderer::draw(
+                renderer,
+                defaults,
+                Rectangle {
+                    x: text_bounds.x,
+                    y: text_bounds.y + i as f32 * height - self.state.scroll,
+                    width: text_bounds.width,
+                    height,
+                },
+                content,
+                self.size,
+                Renderer::Font::default(),
+                None,
+                HorizontalAlignment::Left,
+                VerticalAlignment::Top,
+            )
+        };
+        let first = (self.state.scroll / height) as usize;
+        let shown = (text_bounds.height / height).ceil() as usize + 1;
+        let mut output = draw_line(renderer, first, "");
+        for (i, line) in lines.iter().enumerate().skip(first).take(shown) {
+            let line = draw_line(renderer, i, &self.value[line.clone()]);
+            output = renderer.overlay(output, line, text_bounds);
+        }
+
+        if self.state.focused {
+            let cursor = clamp_cursor(&self.value, self.state.cursor);
+            let i = line_of(&lines, cursor);
+            let x = self.measure(renderer, &self.value[lines[i].start..cursor]);
+            let caret = self
+                .caret
+                .layout(renderer, &layout::Limits::new(Size::ZERO, bounds.size()));
+            let offset = Vector::new(
+                text_bounds.x + x,
+                text_bounds.y + i as f32 * height - self.state.scroll,
+            );
+            let caret = self.caret.draw(
+                renderer,
+                defaults,
+                Layout::with_offset(offset, &caret),
+                cursor_position,
+                viewport,
+            );
+            output = renderer.overlay(output, caret, text_bounds);
+        }
+
+        output
+    }
+
+    fn hash_layout(&self, state: &mut Hasher) {
+        struct Marker;
+        std::any::TypeId::of::<Marker>().hash(state);
+    }
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        messages: &mut Vec<Message>,
+    ) -> event::Status {
+        let text_bounds = self.text_bounds(layout);
+        let cursor = clamp_cursor(&self.value, self.state.cursor);
+        match event {
+            Event::Mouse(mouse::Event::WheelScrolled { delta })
+                if layout.bounds().contains(cursor_position) =>
+            {
+                let lines = self.lines(renderer, text_bounds.width).len();
+                let delta = match delta {
+                    mouse::ScrollDelta::Lines { y, .. } => y * 3.0 * f32::from(self.size),
+                    mouse::ScrollDelta::Pixels { y, .. } => y,
+                };
+                self.scroll(-delta, lines, text_bounds);
+            }
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                if !layout.bounds().contains(cursor_position) {
+                    self.blur(messages);
+
+                    return event::Status::Ignored;
+                }
+
+                // Put the cursor at the nearest character to the click
+                let lines = self.lines(renderer, text_bounds.width);
+                let y = cursor_position.y - text_bounds.y + self.state.scroll;
+                let i = ((y / f32::from(self.size)).max(0.0) as usize).min(lines.len() - 1);
+                let x = cursor_position.x - text_bounds.x;
+                self.state.cursor = hit(&self.value, lines[i].clone(), x, |s| {
+                    self.measure(renderer, s)
+                });
+                self.state.focused = true;
+                messages.push(self.on_move.clone());
+            }
+            Event::Keyboard(keyboard::Event::CharacterReceived(c))
+                if self.state.focused && !c.is_control() =>
+            {
+                self.insert(cursor, c.encode_utf8(&mut [0; 4]), messages);
+                self.scroll_to_cursor(renderer, text_bounds);
+            }
+            Event::Keyboard(keyboard::Event::KeyPressed {
+                key_code,
+                modifiers,
+            }) if self.state.focused => {
+                let value = &self.value;
+                let moved = match key_code {
+                    keyboard::KeyCode::Enter | keyboard::KeyCode::NumpadEnter => {
+                        self.insert(cursor, "\n", messages);
+                        None
+                    }
+                    keyboard::KeyCode::Backspace => {
+                        if let Some(start) = prev(value, cursor) {
+                            self.remove(start, cursor, messages);
+                        }
+                        None
+                    }
+                    keyboard::KeyCode::Delete => {
+                        if let Some(end) = next(value, cursor) {
+                            self.remove(cursor, end, messages);
+                        }
+                        None
+                    }
+                    keyboard::KeyCode::V if modifiers.is_command_pressed() => {
+                        if let Some(pasted) = clipboard.read() {
+                            let pasted: String = pasted
+                                .replace("\r\n", "\n")
+                                .chars()
+                                .filter(|c| *c == '\n' || !c.is_control())
+                                .collect();
+                            self.insert(cursor, &pasted, messages);
+                        }
+                        None
+                    }
+                    keyboard::KeyCode::Escape => {
+                        self.blur(messages);
+                        None
+                    }
+                    keyboard::KeyCode::Left => prev(value, cursor),
+                    keyboard::KeyCode::Right => next(value, cursor),
+                    keyboard::KeyCode::Home => Some(line_start(value, cursor)),
+                    keyboard::KeyCode::End => Some(line_end(value, cursor)),
+                    keyboard::KeyCode::Up => Some(up(value, cursor)),
+                    keyboard::KeyCode::Down => Some(down(value, cursor)),
+                    _ => None,
+                };
+
+                if let Some(moved) = moved {
+                    self.state.cursor = moved;
+                    messages.push(self.on_move.clone());
+                }
+                self.scroll_to_cursor(renderer, text_bounds);
+            }
+            _ => return event::Status::Ignored,
+        }
+
+        event::Status::Captured
+    }
+}
+
+impl<'a, Message, Renderer> From<MultilineInput<'a, Message, Renderer>>
+    for Element<'a, Message, Renderer>
+where
+    Message: 'a + Clone,
+    Renderer: 'a + text::Renderer,
+{
+    fn from(input: MultilineInput<'a, Message, Renderer>) -> Self {
+        Element::new(input)
+    }
+}
+
+/// Split text into the lines it's shown as, no wider than `width` where possible.
+/// Lines are broken at newlines, then after the last whitespace that fits, or mid-word if a word is too long.
+/// Whitespace can hang off the end of a line. Returns the range of each line, without its newline.
+fn wrap(value: &str, width: f32, measure: impl Fn(&str) -> f32) -> Vec<Range<usize>> {
+    let mut lines = vec![];
+    let mut start = 0;
+    for paragraph in value.split('\n') {
+        let end = start + paragraph.len();
+        let mut line_start = start;
+        let mut after_space = None;
+        for (i, c) in paragraph.char_indices() {
+            let i = start + i;
+            let next = i + c.len_utf8();
+            while !c.is_whitespace() && i > line_start && measure(&value[line_start..next]) > width
+            {
+                let split = match after_space {
+                    Some(after) if after > line_start => after,
+                    _ => i,
+                };
+                lines.push(line_start..split);
+                line_start = split;
+                after_space = None;
+            }
+
+            if c.is_whitespace() {
+                after_space = Some(next);
+            }
+        }
+        lines.push(line_start..end);
+        start = end + 1;
+    }
+
+    lines
+}
+
+/// Which line a cursor is on. At a line break, that's the start of the later line.
+fn line_of(lines: &[Range<usize>], cursor: usize) -> usize {
+    lines
+        .iter()
+        .rposition(|line| line.start <= cursor)
+        .unwrap_or(0)
+}
+
+/// The position in a line nearest to `x` pixels from its start
+fn hit(value: &str, line: Range<usize>, x: f32, measure: impl Fn(&str) -> f32) -> usize {
+    let text = &value[line.clone()];
+    let mut nearest = (line.start, x.abs());
+    for (i, c) in text.char_indices() {
+        let end = i + c.len_utf8();
+        let distance = (measure(&text[..end]) - x).abs();
+        if distance < nearest.1 {
+            nearest = (line.start + end, distance);
+        }
+    }
+
+    nearest.0
+}
+
+/// Move a cursor back onto the text and onto a character boundary, such as after the text changes from elsewhere.
+fn clamp_cursor(value: &str, cursor: usize) -> usize {
+    let mut cursor = cursor.min(value.len());
+    while !value.is_char_boundary(cursor) {
+        cursor -= 1;
+    }
+
+    cursor
+}
+
+/// The position of the character before the cursor
+fn prev(value: &str, cursor: usize) -> Option<usize> {
+    value[..cursor]
+        .chars()
+        .next_back()
+        .map(|c| cursor - c.len_utf8())
+}
+
+/// The position after the character after the cursor
+fn next(value: &str, cursor: usize) -> Option<usize> {
+    value[cursor..]
+        .chars()
+        .next()
+        .map(|c| cursor + c.len_utf8())
+}
+
+/// The start of the line the cursor is on
+fn line_start(value: &str, cursor: usize) -> usize {
+    value[..cursor].rfind('\n').map_or(0, |i| i + 1)
+}
+
+/// The end of the line the cursor is on, before its newline
+fn line_end(value: &str, cursor: usize) -> usize {
+    value[cursor..]
+        .find('\n')
+        .map_or(value.len(), |i| cursor + i)
+}
+
+/// Move `n` characters from the start of a line, stopping at its end
+fn column(value: &str, start: usize, n: usize) -> usize {
+    let end = line_end(value, start);
+    value[start..end]
+        .char_indices()
+        .nth(n)
+        .map_or(end, |(i, _)| start + i)
+}
+
+/// The same column on the line above, or the start if this is the first line
+fn up(value: &str, cursor: usize) -> usize {
+    let start = line_start(value, cursor);
+    if start == 0 {
+        return 0;
+    }
+
+    let n = value[start..cursor].chars().count();
+    column(value, line_start(value, start - 1), n)
+}
+
+/// The same column on the line below, or the end if this is the last line
+fn down(value: &str, cursor: usize) -> usize {
+    let end = line_end(value, cursor);
+    if end == value.len() {
+        return end;
+    }
+
+    let n = value[line_start(value, cursor)..cursor].chars().count();
+    column(value, end + 1, n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Byte offsets: "h" 0, "é" 1, "l" 3, "l" 4, "o" 5, "\n" 6, "a" 7, "b" 8, "\n" 9,
+    // "日" 10, "本" 13, "語" 16, "x" 19, end 20
+    const TEXT: &str = "héllo\nab\n日本語x";
+
+    #[test]
+    fn clamp() {
+        assert_eq!(clamp_cursor(TEXT, 2), 1);
+        assert_eq!(clamp_cursor(TEXT, 12), 10);
+        assert_eq!(clamp_cursor(TEXT, 100), 20);
+    }
+
+    #[test]
+    fn prev_next() {
+        assert_eq!(prev(TEXT, 0), None);
+        assert_eq!(prev(TEXT, 3), Some(1));
+        assert_eq!(prev(TEXT, 7), Some(6));
+        assert_eq!(prev(TEXT, 13), Some(10));
+
+        assert_eq!(next(TEXT, 1), Some(3));
+        assert_eq!(next(TEXT, 6), Some(7));
+        assert_eq!(next(TEXT, 16), Some(19));
+        assert_eq!(next(TEXT, 20), None);
+    }
+
+    #[test]
+    fn line_bounds() {
+        assert_eq!(line_start(TEXT, 3), 0);
+        assert_eq!(line_end(TEXT, 3), 6);
+
+        // On a newline, the cursor is at the end of its line
+        assert_eq!(line_start(TEXT, 6), 0);
+        assert_eq!(line_end(TEXT, 6), 6);
+
+        assert_eq!(line_start(TEXT, 8), 7);
+        assert_eq!(line_end(TEXT, 7), 9);
+
+        assert_eq!(line_start(TEXT, 20), 10);
+        assert_eq!(line_end(TEXT, 13), 20);
+    }
+
+    #[test]
+    fn up_down() {
+        // Same column, counted in characters rather than bytes
+        assert_eq!(up(TEXT, 13), 8);
+        assert_eq!(down(TEXT, 8), 13);
+        assert_eq!(down(TEXT, 3), 9);
+
+        // Moving onto a shorter line stops at its end
+        assert_eq!(down(TEXT, 5), 9);
+        assert_eq!(up(TEXT, 19), 9);
+
+        // Off the top or bottom goes to the start or end
+        assert_eq!(up(TEXT, 3), 0);
+        assert_eq!(down(TEXT, 13), 20);
+    }
+
+    #[test]
+    fn empty_lines() {
+        let text = "ab\n\ncd";
+        assert_eq!(down(text, 2), 3);
+        assert_eq!(down(text, 3), 4);
+        assert_eq!(up(text, 6), 3);
+        assert_eq!(up(text, 3), 0);
+        assert_eq!(line_start(text, 3), 3);
+        assert_eq!(line_end(text, 3), 3);
+
+        assert_eq!(up("", 0), 0);
+        assert_eq!(down("", 0), 0);
+    }
+
+    /// Every character is one pixel wide
+    fn chars(s: &str) -> f32 {
+        s.chars().count() as f32
+    }
+
+    fn lines(value: &str, width: f32) -> Vec<&str> {
+        wrap(value, width, chars)
+            .into_iter()
+            .map(|line| &value[line])
+            .collect()
+    }
+
+    #[test]
+    fn wrap_words() {
+        assert_eq!(lines("one two three", 6.0), ["one ", "two ", "three"]);
+        assert_eq!(lines("one two three", 8.0), ["one two ", "three"]);
+        assert_eq!(lines(TEXT, 3.0), ["hél", "lo", "ab", "日本語", "x"]);
+        assert_eq!(lines("ab\n\ncd", 10.0), ["ab", "", "cd"]);
+        assert_eq!(lines("", 10.0), [""]);
+    }
+
+    #[test]
+    fn wrap_long_words() {
+        assert_eq!(lines("abcdefg", 3.0), ["abc", "def", "g"]);
+        assert_eq!(lines("a bcdefg", 3.0), ["a ", "bcd", "efg"]);
+        assert_eq!(lines("ab   cd", 3.0), ["ab   ", "cd"]);
+    }
+
+    #[test]
+    fn line_of_cursor() {
+        let lines = wrap("one two\nthree", 5.0, chars);
+        assert_eq!(line_of(&lines, 0), 0);
+        assert_eq!(line_of(&lines, 3), 0);
+
+        // Where a line is wrapped, the cursor goes on the later one
+        assert_eq!(line_of(&lines, 4), 1);
+        assert_eq!(line_of(&lines, 7), 1);
+        assert_eq!(line_of(&lines, 8), 2);
+        assert_eq!(line_of(&lines, 13), 2);
+    }
+
+    #[test]
+    fn hit_nearest() {
+        assert_eq!(hit(TEXT, 0..6, -1.0, chars), 0);
+        assert_eq!(hit(TEXT, 0..6, 1.4, chars), 1);
+        assert_eq!(hit(TEXT, 0..6, 1.6, chars), 3);
+        assert_eq!(hit(TEXT, 0..6, 10.0, chars), 6);
+        assert_eq!(hit(TEXT, 10..20, 2.0, chars), 16);
+        assert_eq!(hit(TEXT, 9..9, 2.0, chars), 9);
+    }
+}
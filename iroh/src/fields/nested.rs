@@ -0,0 +1,78 @@
+use crate::{
+    app::AppState,
+    fields::{indented, lift, toggle::Toggle},
+    lens::Lens,
+    message::Message,
+    Field, Kind, ObjectStore,
+};
+use iced::{Column, Element, Length, Row, Subscription, Text};
+use std::{fmt::Debug, marker::PhantomData};
+
+/// A field for a struct inside an object, which is edited with its own fields.
+/// `L` gets the inner struct, and `F` is the field for it, such as a [`crate::kinds::ConsFields`] of its fields.
+/// The inner fields are shown as an indented group, which can be collapsed by clicking its name.
+#[derive(Debug, Clone)]
+pub struct NestedField<const N: &'static str, L, F> {
+    inner: F,
+    expanded: bool,
+    _d: PhantomData<L>,
+}
+
+impl<K, L, F, const N: &'static str> Field<K> for NestedField<N, L, F>
+where
+    K: Kind,
+    L: 'static + Lens,
+    L::Source: 'static + Debug + Clone + Send,
+    L::Target: 'static + Debug + Clone + Send,
+    F: Field<K, Source = L::Target>,
+{
+    type Source = L::Source;
+    type WorkingValues = F::WorkingValues;
+
+    fn view<C: ObjectStore<K>>(
+        &mut self,
+        key: &K::Key,
+        val: &L::Source,
+        app_state: &AppState<K, C>,
+        working: &F::WorkingValues,
+    ) -> Vec<Element<Message<K, Self::WorkingValues, L::Source>>> {
+        let expanded = self.expanded;
+        let heading = Text::new(format!("{} {}", if expanded { "-" } else { "+" }, N))
+            .color(app_state.theme().text_accent());
+        let mut col = Column::new()
+            .spacing(4)
+            .push(Row::new().padding(8).push(Toggle::new(
+                &mut self.expanded,
+                heading,
+                Message::Nop,
+            )));
+
+        if expanded {
+            // The inner field mutates the inner struct, so we need to lift that to our source
+            let mut inner = Column::new().spacing(4).width(Length::Fill);
+            for e in self.inner.view(key, L::get(val), app_state, working) {
+                inner = inner.push(e.map(lift::<K, L, F::WorkingValues>));
+            }
+
+            col = col.push(indented(inner));
+        }
+
+        vec![col.into()]
+    }
+
+    fn subscription(&self) -> Subscription<Message<K, Self::WorkingValues, L::Source>> {
+        self.inner
+            .subscription()
+            .map(lift::<K, L, F::WorkingValues>)
+    }
+}
+
+impl<const N: &'static str, L, F: Default> Default for NestedField<N, L, F> {
+    fn default() -> Self {
+        Self {
+            inner: F::default(),
+            expanded: true,
+            _d: PhantomData,
+        }
+    }
+}
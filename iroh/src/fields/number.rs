@@ -0,0 +1,71 @@
+use crate::{fields::text_input::FromTextInput, lens::Lens};
+use std::{cmp::Ordering, fmt::Debug};
+
+/// A number that can be edited with a [`super::SliderField`] or [`super::NumberSpinnerField`].
+/// Values are converted to and from `f64` while they're being dragged around.
+pub trait Number: 'static + Copy + PartialOrd + Debug + Send + FromTextInput {
+    fn to_f64(self) -> f64;
+
+    /// Convert back from an `f64`, rounding if needed
+    fn from_f64(x: f64) -> Self;
+}
+
+macro_rules! float_number {
+    ($($t:ty),*) => {
+        $(impl Number for $t {
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+
+            fn from_f64(x: f64) -> Self {
+                x as $t
+            }
+        })*
+    };
+}
+
+macro_rules! int_number {
+    ($($t:ty),*) => {
+        $(impl Number for $t {
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+
+            fn from_f64(x: f64) -> Self {
+                // `as` saturates, so this can't overflow
+                x.round() as $t
+            }
+        })*
+    };
+}
+
+float_number!(f32, f64);
+int_number!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+/// The values a numeric field allows, and how much it changes by at once.
+/// This is implemented on the lens, so each field of a struct can have its own range.
+pub trait NumberRange: Lens
+where
+    Self::Target: Number,
+{
+    const MIN: Self::Target;
+    const MAX: Self::Target;
+
+    /// How much the buttons, slider and scrubbing change the value by
+    const STEP: Self::Target;
+
+    /// Get the closest value to `value` that's in range.
+    /// Values that can't be compared, such as NaN, become the minimum.
+    fn clamp(value: Self::Target) -> Self::Target {
+        match value.partial_cmp(&Self::MIN) {
+            Some(Ordering::Less) | None => Self::MIN,
+            _ if value > Self::MAX => Self::MAX,
+            _ => value,
+        }
+    }
+
+    /// Whether `value` is in range
+    fn contains(value: Self::Target) -> bool {
+        value >= Self::MIN && value <= Self::MAX
+    }
+}
@@ -0,0 +1,98 @@
+use crate::{
+    app::AppState,
+    fields::{indented, lift},
+    lens::{Lens, RootLens},
+    message::Message,
+    mutation::{LensSet, SomeMutation},
+    Field, Kind, ObjectStore,
+};
+use iced::{Align, Checkbox, Column, Element, Length, Row, Subscription, Text};
+use std::{fmt::Debug, marker::PhantomData};
+
+/// A field for an Option, with a checkbox for whether it's set, and the inner field `F` when it is.
+/// `L` gets the Option. Setting it starts from `T::default()`, and clearing it resets the working values.
+#[derive(Debug, Clone)]
+pub struct OptionField<const N: &'static str, L, F> {
+    inner: F,
+    _d: PhantomData<L>,
+}
+
+impl<K, L, T, F, const N: &'static str> Field<K> for OptionField<N, L, F>
+where
+    K: Kind,
+    L: 'static + Lens<Target = Option<T>>,
+    L::Source: 'static + Debug + Clone + Send,
+    T: 'static + Default + Debug + Clone + Send,
+    F: Field<K, Source = T>,
+{
+    type Source = L::Source;
+    type WorkingValues = F::WorkingValues;
+
+    fn view<C: ObjectStore<K>>(
+        &mut self,
+        key: &K::Key,
+        val: &L::Source,
+        app_state: &AppState<K, C>,
+        working: &F::WorkingValues,
+    ) -> Vec<Element<Message<K, Self::WorkingValues, L::Source>>> {
+        let value = L::get(val);
+        let mut col = Column::new().spacing(4).push(
+            Row::with_children(vec![
+                Text::new(N).color(app_state.theme().text_primary()).into(),
+                Checkbox::new(value.is_some(), "", |set| {
+                    Message::Mutate(
+                        Box::new(LensSet::<L>::new(if set {
+                            Some(T::default())
+                        } else {
+                            None
+                        })),
+                        Box::new(LensSet::<RootLens<_>>::new(F::WorkingValues::default())),
+                    )
+                })
+                .style(app_state.theme().checkbox())
+                .into(),
+            ])
+            .spacing(4)
+            .padding(8)
+            .align_items(Align::Center),
+        );
+
+        if let Some(value) = value {
+            let mut inner = Column::new().spacing(4).width(Length::Fill);
+            for e in self.inner.view(key, value, app_state, working) {
+                inner = inner.push(e.map(lift_some::<K, L, T, F::WorkingValues>));
+            }
+
+            col = col.push(indented(inner));
+        }
+
+        vec![col.into()]
+    }
+
+    fn subscription(&self) -> Subscription<Message<K, Self::WorkingValues, L::Source>> {
+        self.inner
+            .subscription()
+            .map(lift_some::<K, L, T, F::WorkingValues>)
+    }
+}
+
+impl<const N: &'static str, L, F: Default> Default for OptionField<N, L, F> {
+    fn default() -> Self {
+        Self {
+            inner: F::default(),
+            _d: PhantomData,
+        }
+    }
+}
+
+/// Lift a message from the inner field to one for the whole Option
+fn lift_some<K, L, T, W>(m: Message<K, W, T>) -> Message<K, W, L::Source>
+where
+    K: Kind,
+    L: 'static + Lens<Target = Option<T>>,
+    L::Source: 'static,
+    T: 'static + Debug + Clone + Send,
+    W: 'static + Default + Debug + Clone + Send,
+{
+    lift::<K, L, W>(m.map_value(|v| Box::new(SomeMutation::new(v))))
+}
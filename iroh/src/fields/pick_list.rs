@@ -0,0 +1,88 @@
+use crate::{
+    app::AppState,
+    lens::Lens,
+    message::Message,
+    mutation::{LensSet, NopMutator},
+    Field, Kind, ObjectStore,
+};
+use iced::{pick_list, Align, Element, PickList, Row, Text};
+use std::{
+    fmt::{Debug, Display},
+    marker::PhantomData,
+};
+
+/// A type with a fixed set of values, such as a C-like enum, which can be picked from a list.
+/// This can be derived for enums without fields using `#[derive(Pickable)]` from iroh-codegen.
+pub trait Pickable: 'static + Sized + Clone + Eq + Debug + Send {
+    /// Every value, in the order they should be listed
+    const VARIANTS: &'static [Self];
+
+    /// The name to show for this value
+    fn name(&self) -> &'static str;
+}
+
+/// A field for [`Pickable`] values, which the user chooses from a dropdown.
+/// Uses a lens to get/set the right field on the struct.
+#[derive(Debug, Clone)]
+pub struct PickListField<const N: &'static str, L: Lens> {
+    list_state: pick_list::State<Choice<L::Target>>,
+    _d: PhantomData<L>,
+}
+
+impl<K: Kind, L: 'static + Lens, const N: &'static str> Field<K> for PickListField<N, L>
+where
+    L::Source: 'static + Debug + Clone + Send,
+    L::Target: Pickable,
+{
+    type Source = L::Source;
+    type WorkingValues = ();
+
+    fn view<C: ObjectStore<K>>(
+        &mut self,
+        _key: &K::Key,
+        val: &L::Source,
+        app_state: &AppState<K, C>,
+        _working: &(),
+    ) -> Vec<Element<Message<K, Self::WorkingValues, L::Source>>> {
+        let options = L::Target::VARIANTS
+            .iter()
+            .cloned()
+            .map(Choice)
+            .collect::<Vec<_>>();
+
+        vec![Row::with_children(vec![
+            Text::new(N).color(app_state.theme().text_primary()).into(),
+            PickList::new(
+                &mut self.list_state,
+                options,
+                Some(Choice(L::get(val).clone())),
+                |Choice(v)| Message::Mutate(Box::new(LensSet::<L>::new(v)), Box::new(NopMutator)),
+            )
+            .style(app_state.theme().pick_list())
+            .into(),
+        ])
+        .spacing(4)
+        .padding(8)
+        .align_items(Align::Center)
+        .into()]
+    }
+}
+
+impl<const N: &'static str, L: Lens> Default for PickListField<N, L> {
+    fn default() -> Self {
+        Self {
+            list_state: pick_list::State::default(),
+            _d: PhantomData,
+        }
+    }
+}
+
+/// Shows a [`Pickable`] by its name, since that's what [`PickList`] displays.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Choice<T>(T);
+
+impl<T: Pickable> Display for Choice<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.name())
+    }
+}
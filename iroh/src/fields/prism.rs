@@ -0,0 +1,58 @@
+use crate::{
+    app::AppState, lens::Prism, message::Message, mutation::PrismMutation, Field, Kind, ObjectStore,
+};
+use iced::{Element, Subscription};
+use std::{fmt::Debug, marker::PhantomData};
+
+/// A field for something a prism gets, such as the fields of one enum variant, which is edited with `F`.
+/// When the prism doesn't match, nothing is shown, and edits from `F` do nothing.
+#[derive(Debug, Clone)]
+pub struct PrismField<P, F> {
+    inner: F,
+    _d: PhantomData<P>,
+}
+
+impl<K, P, F> Field<K> for PrismField<P, F>
+where
+    K: Kind,
+    P: 'static + Prism,
+    P::Source: 'static + Debug + Clone + Send,
+    P::Target: 'static + Debug + Clone + Send,
+    F: Field<K, Source = P::Target>,
+{
+    type Source = P::Source;
+    type WorkingValues = F::WorkingValues;
+
+    fn view<C: ObjectStore<K>>(
+        &mut self,
+        key: &K::Key,
+        val: &P::Source,
+        app_state: &AppState<K, C>,
+        working: &F::WorkingValues,
+    ) -> Vec<Element<Message<K, Self::WorkingValues, P::Source>>> {
+        match P::get(val) {
+            Some(inner) => self
+                .inner
+                .view(key, inner, app_state, working)
+                .into_iter()
+                .map(|e| e.map(|m| m.map_value(|v| Box::new(PrismMutation::<P>::new(v)))))
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    fn subscription(&self) -> Subscription<Message<K, Self::WorkingValues, P::Source>> {
+        self.inner
+            .subscription()
+            .map(|m| m.map_value(|v| Box::new(PrismMutation::<P>::new(v))))
+    }
+}
+
+impl<P, F: Default> Default for PrismField<P, F> {
+    fn default() -> Self {
+        Self {
+            inner: F::default(),
+            _d: PhantomData,
+        }
+    }
+}
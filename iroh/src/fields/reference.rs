@@ -0,0 +1,149 @@
+use crate::{
+    app::AppState,
+    lens::Lens,
+    message::Message,
+    mutation::{LensSet, NopMutator},
+    Field, Key, Kind, ObjectStore,
+};
+use iced::{button, pick_list, Align, Button, Element, PickList, Row, Text};
+use std::{
+    fmt::{Debug, Display},
+    marker::PhantomData,
+};
+
+/// A property which refers to another object by its key, or possibly to nothing.
+/// This is implemented for keys themselves and for `Option`s of them.
+pub trait Reference<Q: Key>: 'static + Sized + Clone + Eq + Debug + Send {
+    /// The key this refers to, if any
+    fn key(&self) -> Option<&Q>;
+
+    /// Refer to the given key, or to nothing. Returns `None` if this can't refer to nothing.
+    fn from_key(key: Option<Q>) -> Option<Self>;
+}
+
+impl<Q: Key> Reference<Q> for Q {
+    fn key(&self) -> Option<&Q> {
+        Some(self)
+    }
+
+    fn from_key(key: Option<Q>) -> Option<Self> {
+        key
+    }
+}
+
+impl<Q: Key> Reference<Q> for Option<Q> {
+    fn key(&self) -> Option<&Q> {
+        self.as_ref()
+    }
+
+    fn from_key(key: Option<Q>) -> Option<Self> {
+        Some(key)
+    }
+}
+
+/// A field for references to other objects in the store, which the user picks from a dropdown of keys.
+/// References to objects that don't exist are flagged, and the object referred to can be selected with "Go to".
+#[derive(Debug, Clone)]
+pub struct ReferenceField<const N: &'static str, L: Lens> {
+    list_state: pick_list::State<KeyChoice<L::Target>>,
+    go_to_state: button::State,
+    _d: PhantomData<L>,
+}
+
+impl<K: Kind, L: 'static + Lens, const N: &'static str> Field<K> for ReferenceField<N, L>
+where
+    L::Source: 'static + Debug + Clone + Send,
+    L::Target: Reference<K::Key>,
+{
+    type Source = L::Source;
+    type WorkingValues = ();
+
+    fn view<C: ObjectStore<K>>(
+        &mut self,
+        _key: &K::Key,
+        val: &L::Source,
+        app_state: &AppState<K, C>,
+        _working: &(),
+    ) -> Vec<Element<Message<K, Self::WorkingValues, L::Source>>> {
+        let theme = app_state.theme();
+        let value = L::get(val);
+
+        // Only offer nothing if it can be set
+        let options = L::Target::from_key(None)
+            .into_iter()
+            .chain(
+                app_state
+                    .container()
+                    .keys()
+                    .filter_map(|k| L::Target::from_key(Some(k.clone()))),
+            )
+            .map(KeyChoice::new)
+            .collect::<Vec<_>>();
+
+        let mut row = Row::with_children(vec![
+            Text::new(N).color(theme.text_primary()).into(),
+            PickList::new(
+                &mut self.list_state,
+                options,
+                Some(KeyChoice::new(value.clone())),
+                |c| Message::Mutate(Box::new(LensSet::<L>::new(c.value)), Box::new(NopMutator)),
+            )
+            .style(theme.pick_list())
+            .into(),
+        ]);
+
+        let mut go_to =
+            Button::new(&mut self.go_to_state, Text::new("Go to")).style(theme.button_subtle());
+        match value.key() {
+            Some(k) if app_state.container().exists(k) => {
+                go_to = go_to.on_press(Message::Select(k.clone()))
+            }
+            Some(_) => row = row.push(Text::new("Missing").color(theme.text_error())),
+            None => (),
+        }
+
+        vec![row
+            .push(go_to)
+            .spacing(4)
+            .padding(8)
+            .align_items(Align::Center)
+            .into()]
+    }
+}
+
+impl<const N: &'static str, L: Lens> Default for ReferenceField<N, L> {
+    fn default() -> Self {
+        Self {
+            list_state: pick_list::State::default(),
+            go_to_state: button::State::default(),
+            _d: PhantomData,
+        }
+    }
+}
+
+/// Shows a key the same way the outline does, or "None" for an empty reference.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyChoice<T> {
+    value: T,
+    label: String,
+}
+
+impl<T> KeyChoice<T> {
+    fn new<Q: Key>(value: T) -> Self
+    where
+        T: Reference<Q>,
+    {
+        let label = match value.key() {
+            Some(k) => format!("{:?}", k),
+            None => "None".to_string(),
+        };
+
+        Self { value, label }
+    }
+}
+
+impl<T> Display for KeyChoice<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label)
+    }
+}
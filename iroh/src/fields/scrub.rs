@@ -0,0 +1,130 @@
+//! A widget which changes a number when dragged left or right.
+
+use iced_native::{event, mouse, Clipboard, Element, Event, Layout, Point, Widget};
+
+/// How far the cursor has to move to change the value by one step
+const PIXELS_PER_STEP: f32 = 4.0;
+
+/// Whether a [`Scrub`] is being dragged
+#[derive(Debug, Clone, Copy, Default)]
+pub struct State {
+    drag: Option<Drag>,
+}
+
+/// A drag in progress
+#[derive(Debug, Clone, Copy)]
+struct Drag {
+    /// Where the cursor was when the drag started
+    x: f32,
+
+    /// What the value was when the drag started
+    start: f64,
+
+    /// How many steps the value has been moved by so far
+    steps: f64,
+}
+
+/// Wraps some content, usually a label, so that dragging it changes a value by steps.
+pub struct Scrub<'a, Message, Renderer> {
+    state: &'a mut State,
+    content: Element<'a, Message, Renderer>,
+    value: f64,
+    step: f64,
+    on_change: Box<dyn Fn(f64) -> Message>,
+    on_release: Option<Message>,
+}
+
+impl<'a, Message, Renderer> Scrub<'a, Message, Renderer> {
+    /// Create a scrub area around `content`, for the given value.
+    /// `on_change` is called with the new value as it's dragged.
+    pub fn new<F>(
+        state: &'a mut State,
+        content: impl Into<Element<'a, Message, Renderer>>,
+        value: f64,
+        step: f64,
+        on_change: F,
+    ) -> Self
+    where
+        F: 'static + Fn(f64) -> Message,
+    {
+        Self {
+            state,
+            content: content.into(),
+            value,
+            step,
+            on_change: Box::new(on_change),
+            on_release: None,
+        }
+    }
+
+    /// Set the message to produce when dragging stops
+    pub fn on_release(mut self, message: Message) -> Self {
+        self.on_release = Some(message);
+        self
+    }
+}
+
+impl<'a, Message: Clone, Renderer: iced_native::Renderer> Widget<Message, Renderer>
+    for Scrub<'a, Message, Renderer>
+{
+    wrap_content!();
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        messages: &mut Vec<Message>,
+    ) -> event::Status {
+        match (event.clone(), self.state.drag) {
+            (Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)), None)
+                if layout.bounds().contains(cursor_position) =>
+            {
+                self.state.drag = Some(Drag {
+                    x: cursor_position.x,
+                    start: self.value,
+                    steps: 0.0,
+                });
+
+                event::Status::Captured
+            }
+            (Event::Mouse(mouse::Event::CursorMoved { position }), Some(drag)) => {
+                let steps = ((position.x - drag.x) / PIXELS_PER_STEP).trunc() as f64;
+                if steps != drag.steps {
+                    self.state.drag = Some(Drag { steps, ..drag });
+                    messages.push((self.on_change)(drag.start + steps * self.step));
+                }
+
+                event::Status::Captured
+            }
+            (Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)), Some(_)) => {
+                self.state.drag = None;
+                if let Some(m) = &self.on_release {
+                    messages.push(m.clone());
+                }
+
+                event::Status::Captured
+            }
+            _ => self.content.on_event(
+                event,
+                layout,
+                cursor_position,
+                renderer,
+                clipboard,
+                messages,
+            ),
+        }
+    }
+}
+
+impl<'a, Message, Renderer> From<Scrub<'a, Message, Renderer>> for Element<'a, Message, Renderer>
+where
+    Message: 'a + Clone,
+    Renderer: 'a + iced_native::Renderer,
+{
+    fn from(scrub: Scrub<'a, Message, Renderer>) -> Self {
+        Element::new(scrub)
+    }
+}
@@ -0,0 +1,91 @@
+use crate::{
+    app::AppState,
+    fields::{
+        drag::{self, DragWatch},
+        number::{Number, NumberRange},
+    },
+    message::Message,
+    mutation::{LensSet, NopMutator, Session},
+    Field, Kind, ObjectStore,
+};
+use iced::{slider, Align, Element, Length, Row, Slider, Text};
+use std::{fmt::Debug, marker::PhantomData};
+
+/// A field for numbers, which the user sets by dragging a slider.
+/// The range and step come from the lens, which must implement [`NumberRange`].
+/// Each drag is undone as one step.
+#[derive(Debug, Clone)]
+pub struct SliderField<const N: &'static str, L> {
+    slider_state: slider::State,
+    drag_state: drag::State,
+    session: Session,
+    _d: PhantomData<L>,
+}
+
+impl<K: Kind, L: 'static + NumberRange, const N: &'static str> Field<K> for SliderField<N, L>
+where
+    L::Source: 'static + Debug + Clone + Send,
+    L::Target: Number,
+{
+    type Source = L::Source;
+    type WorkingValues = ();
+
+    fn view<C: ObjectStore<K>>(
+        &mut self,
+        _key: &K::Key,
+        val: &L::Source,
+        app_state: &AppState<K, C>,
+        _working: &(),
+    ) -> Vec<Element<Message<K, Self::WorkingValues, L::Source>>> {
+        let value = *L::get(val);
+        // The first message of a drag is made by the view from before it started, so a fresh session is ready
+        if !self.drag_state.is_dragging() {
+            self.session = Session::new();
+        }
+        let session = self.session;
+
+        vec![Row::with_children(vec![
+            Text::new(N).color(app_state.theme().text_primary()).into(),
+            DragWatch::new(
+                &mut self.drag_state,
+                Slider::new(
+                    &mut self.slider_state,
+                    L::MIN.to_f64()..=L::MAX.to_f64(),
+                    value.to_f64(),
+                    move |v| {
+                        Message::Mutate(
+                            Box::new(LensSet::<L>::in_session(
+                                L::clamp(L::Target::from_f64(v)),
+                                session,
+                            )),
+                            Box::new(NopMutator),
+                        )
+                    },
+                )
+                .step(L::STEP.to_f64())
+                .on_release(Message::CommitTransaction)
+                .style(app_state.theme().slider())
+                .width(Length::Fill),
+            )
+            .into(),
+            Text::new(format!("{}", value))
+                .color(app_state.theme().text_primary())
+                .into(),
+        ])
+        .spacing(4)
+        .padding(8)
+        .align_items(Align::Center)
+        .into()]
+    }
+}
+
+impl<const N: &'static str, L> Default for SliderField<N, L> {
+    fn default() -> Self {
+        Self {
+            slider_state: slider::State::default(),
+            drag_state: drag::State::default(),
+            session: Session::new(),
+            _d: PhantomData,
+        }
+    }
+}
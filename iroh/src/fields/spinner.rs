@@ -0,0 +1,156 @@
+use crate::{
+    app::AppState,
+    fields::{
+        focus::{self, FocusWatch},
+        number::{Number, NumberRange},
+        scrub::{self, Scrub},
+        text_input::FromTextInput,
+    },
+    lens::RootLens,
+    message::Message,
+    mutation::{LensSet, NopMutator, Session},
+    Field, Kind, ObjectStore,
+};
+use iced::{button, text_input, Align, Button, Element, Row, Text, TextInput};
+use std::{fmt::Debug, marker::PhantomData};
+
+/// A field for numbers, with a text input and buttons to step the value up & down.
+/// Dragging the label left or right also changes the value.
+/// The range and step come from the lens, which must implement [`NumberRange`].
+/// Anything typed that's out of range is kept as a working value, and never set on the object.
+#[derive(Debug, Clone)]
+pub struct NumberSpinnerField<const N: &'static str, L> {
+    string_value: String,
+    input_state: text_input::State,
+    focus_state: focus::State,
+    scrub_state: scrub::State,
+    decrement_state: button::State,
+    increment_state: button::State,
+    session: Session,
+    was_focused: bool,
+    _d: PhantomData<L>,
+}
+
+impl<K: Kind, L: 'static + NumberRange, const N: &'static str> Field<K> for NumberSpinnerField<N, L>
+where
+    L::Source: 'static + Debug + Clone + Send,
+    L::Target: Number,
+{
+    type Source = L::Source;
+    type WorkingValues = Option<String>;
+
+    fn view<C: ObjectStore<K>>(
+        &mut self,
+        _key: &K::Key,
+        val: &L::Source,
+        app_state: &AppState<K, C>,
+        working: &Option<String>,
+    ) -> Vec<Element<Message<K, Self::WorkingValues, L::Source>>> {
+        let value = *L::get(val);
+        self.string_value = match working {
+            Some(w) => w.clone(),
+            None => format!("{}", value),
+        };
+
+        // Start a new undo step each time the input is focused.
+        // Clicking it produces a message, so this happens before the first keystroke.
+        let focused = self.input_state.is_focused();
+        if focused && !self.was_focused {
+            self.session = Session::new();
+        }
+        self.was_focused = focused;
+
+        let session = self.session;
+        let theme = app_state.theme();
+
+        let mut decrement =
+            Button::new(&mut self.decrement_state, Text::new("-")).style(theme.button_subtle());
+        let mut increment =
+            Button::new(&mut self.increment_state, Text::new("+")).style(theme.button_subtle());
+        if value > L::MIN {
+            decrement = decrement.on_press(set::<K, L>(
+                L::clamp(L::Target::from_f64(value.to_f64() - L::STEP.to_f64())),
+                None,
+            ));
+        }
+        if value < L::MAX {
+            increment = increment.on_press(set::<K, L>(
+                L::clamp(L::Target::from_f64(value.to_f64() + L::STEP.to_f64())),
+                None,
+            ));
+        }
+
+        vec![Row::with_children(vec![
+            Scrub::new(
+                &mut self.scrub_state,
+                Text::new(N).color(theme.text_primary()),
+                value.to_f64(),
+                L::STEP.to_f64(),
+                move |v| set::<K, L>(L::clamp(L::Target::from_f64(v)), Some(session)),
+            )
+            .on_release(Message::CommitTransaction)
+            .into(),
+            FocusWatch::new(
+                &mut self.focus_state,
+                TextInput::new(&mut self.input_state, N, &self.string_value, move |new| {
+                    match L::Target::from_input(&new) {
+                        Ok(v) if L::contains(v) => set::<K, L>(v, Some(session)),
+                        _ => Message::Mutate(
+                            Box::new(NopMutator),
+                            Box::new(LensSet::<RootLens<_>>::in_session(Some(new), session)),
+                        ),
+                    }
+                })
+                .on_submit(Message::CommitTransaction)
+                .style(theme.text_input())
+                .padding(5),
+                Message::Nop,
+                Message::CommitTransaction,
+            )
+            .into(),
+            decrement.into(),
+            increment.into(),
+        ])
+        .spacing(4)
+        .padding(8)
+        .align_items(Align::Center)
+        .into()]
+    }
+}
+
+/// Set the value and clear any working value, optionally as part of an undo session
+fn set<K: Kind, L: 'static + NumberRange>(
+    value: L::Target,
+    session: Option<Session>,
+) -> Message<K, Option<String>, L::Source>
+where
+    L::Source: 'static + Debug + Clone + Send,
+    L::Target: Number,
+{
+    match session {
+        Some(s) => Message::Mutate(
+            Box::new(LensSet::<L>::in_session(value, s)),
+            Box::new(LensSet::<RootLens<_>>::in_session(None, s)),
+        ),
+        None => Message::Mutate(
+            Box::new(LensSet::<L>::new(value)),
+            Box::new(LensSet::<RootLens<_>>::new(None)),
+        ),
+    }
+}
+
+impl<const N: &'static str, L> Default for NumberSpinnerField<N, L> {
+    fn default() -> Self {
+        Self {
+            string_value: "".to_string(),
+            input_state: text_input::State::default(),
+            focus_state: focus::State::default(),
+            scrub_state: scrub::State::default(),
+            decrement_state: button::State::default(),
+            increment_state: button::State::default(),
+            session: Session::new(),
+            was_focused: false,
+            _d: PhantomData,
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use crate::{
+    app::AppState,
+    fields::{
+        multiline::{self, MultilineInput},
+        text_input::FromTextInput,
+    },
+    lens::{Lens, RootLens},
+    message::Message,
+    mutation::{LensSet, NopMutator, Session},
+    Field, Kind, ObjectStore,
+};
+use iced::{Align, Container, Element, Length, Row, Space, Text};
+use std::{fmt::Debug, marker::PhantomData};
+
+/// How tall each line of text is
+const LINE_HEIGHT: u16 = 20;
+
+/// Space between the text and the edge of the box
+const PADDING: u16 = 5;
+
+/// How wide the line showing where the cursor is
+const CARET_WIDTH: u16 = 1;
+
+/// A field which the user sets a value for using a multiline text input.
+/// Long lines are wrapped, and the box is `LINES` lines tall, scrolling if there's more text than fits.
+/// Like [`super::TextInputField`], text that doesn't parse is kept in the working values until it does.
+/// Everything typed while the input has focus is undone as one step.
+#[derive(Debug, Clone)]
+pub struct TextAreaField<const N: &'static str, L, const LINES: u16> {
+    string_value: String,
+    input_state: multiline::State,
+    session: Session,
+    was_focused: bool,
+    _d: PhantomData<L>,
+}
+
+impl<K: Kind, L: 'static + Lens, const N: &'static str, const LINES: u16> Field<K>
+    for TextAreaField<N, L, LINES>
+where
+    L::Source: 'static + Debug + Clone + Send,
+    L::Target: 'static + FromTextInput + Debug + Clone + Send,
+{
+    type Source = L::Source;
+    type WorkingValues = Option<String>;
+
+    fn view<C: ObjectStore<K>>(
+        &mut self,
+        _key: &K::Key,
+        val: &L::Source,
+        app_state: &AppState<K, C>,
+        working: &Option<String>,
+    ) -> Vec<Element<Message<K, Self::WorkingValues, L::Source>>> {
+        self.string_value = working
+            .clone()
+            .unwrap_or_else(|| format!("{}", L::get(val)));
+
+        // Start a new undo step each time the input is focused
+        let focused = self.input_state.is_focused();
+        if focused && !self.was_focused {
+            self.session = Session::new();
+        }
+        self.was_focused = focused;
+
+        let theme = app_state.theme();
+        let caret = Container::new(Space::new(
+            Length::Units(CARET_WIDTH),
+            Length::Units(LINE_HEIGHT),
+        ))
+        .style(theme.caret());
+
+        let session = self.session;
+        let input = MultilineInput::new(
+            &mut self.input_state,
+            &self.string_value,
+            LINE_HEIGHT,
+            caret,
+            move |new| {
+                if let Ok(v) = L::Target::from_input(&new) {
+                    Message::Mutate(
+                        Box::new(LensSet::<L>::in_session(v, session)),
+                        Box::new(LensSet::<RootLens<_>>::in_session(None, session)),
+                    )
+                } else {
+                    Message::Mutate(
+                        Box::new(NopMutator),
+                        Box::new(LensSet::<RootLens<_>>::in_session(Some(new), session)),
+                    )
+                }
+            },
+            Message::Nop,
+        )
+        .padding(PADDING)
+        .on_blur(Message::CommitTransaction);
+
+        vec![Row::with_children(vec![
+            Text::new(N).color(theme.text_primary()).into(),
+            Container::new(input)
+                .width(Length::Fill)
+                .height(Length::Units(LINES * LINE_HEIGHT + 2 * PADDING))
+                .style(theme.text_area(focused))
+                .into(),
+        ])
+        .spacing(4)
+        .padding(8)
+        .align_items(Align::Start)
+        .into()]
+    }
+}
+
+impl<const N: &'static str, L, const LINES: u16> Default for TextAreaField<N, L, LINES> {
+    fn default() -> Self {
+        Self {
+            string_value: "".to_string(),
+            input_state: multiline::State::default(),
+            session: Session::new(),
+            was_focused: false,
+            _d: PhantomData,
+        }
+    }
+}
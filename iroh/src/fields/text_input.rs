@@ -1,8 +1,9 @@
 use crate::{
     app::AppState,
+    fields::focus::{self, FocusWatch},
     lens::{Lens, RootLens},
     message::Message,
-    mutation::{LensSet, NopMutator},
+    mutation::{LensSet, NopMutator, Session},
     Field, Kind, ObjectStore,
 };
 use iced::{text_input, Align, Element, Row, Text, TextInput};
@@ -14,62 +15,97 @@ use std::{
 
 /// A field which the user sets a value for using a text input.
 /// Uses a lens to get/set the right field on the struct.
+/// Everything typed while the input has focus is undone as one step.
 #[derive(Debug, Clone)]
 pub struct TextInputField<const N: &'static str, L> {
     string_value: String,
     input_state: text_input::State,
+    focus_state: focus::State,
+    session: Session,
+    was_focused: bool,
     _d: PhantomData<L>,
 }
-impl<const N: &'static str, L: 'static + Lens> Field for TextInputField<N, L>
+impl<K: Kind, L: 'static + Lens, const N: &'static str> Field<K> for TextInputField<N, L>
 where
-    L::Source: Kind,
+    L::Source: 'static + Debug + Clone + Send,
     L::Target: 'static + FromTextInput + Debug + Clone + Send,
 {
-    type Kind = L::Source;
+    type Source = L::Source;
     type WorkingValues = Option<String>;
 
-    fn view<C: ObjectStore<Self::Kind>>(
+    fn view<C: ObjectStore<K>>(
         &mut self,
-        _key: &<L::Source as Kind>::Key,
+        _key: &K::Key,
         val: &L::Source,
-        app_state: &AppState<Self::Kind, C>,
+        app_state: &AppState<K, C>,
         working: &Option<String>,
-    ) -> Vec<Element<Message<L::Source, Self::WorkingValues>>> {
-        let _out_of_sync = if let Some(w) = working.clone() {
-            self.string_value = w;
+    ) -> Vec<Element<Message<K, Self::WorkingValues, L::Source>>> {
+        // Text that doesn't parse stays in the working values, so say what's wrong with it
+        let error = match working {
+            Some(w) => {
+                self.string_value = w.clone();
 
-            true
-        } else {
-            self.string_value = format!("{}", L::get(val));
+                L::Target::from_input(w).err()
+            }
+            None => {
+                self.string_value = format!("{}", L::get(val));
 
-            false
+                None
+            }
         };
 
-        // TODO: Style based on `_out_of_sync`
+        // Start a new undo step each time the input is focused.
+        // Clicking it produces a message, so this happens before the first keystroke.
+        let focused = self.input_state.is_focused();
+        if focused && !self.was_focused {
+            self.session = Session::new();
+        }
+        self.was_focused = focused;
 
-        vec![Row::with_children(vec![
-            Text::new(N).color(app_state.theme().text_primary()).into(),
-            TextInput::new(&mut self.input_state, N, &self.string_value, |new| {
-                if let Some(v) = L::Target::from_input(&new) {
-                    return Message::Mutate(
-                        Box::new(LensSet::<L>::new(v)),
-                        Box::new(LensSet::<RootLens<_>>::new(None)),
-                    );
+        let theme = app_state.theme();
+        let session = self.session;
+        let input = Row::with_children(vec![
+            Text::new(N).color(theme.text_primary()).into(),
+            FocusWatch::new(
+                &mut self.focus_state,
+                TextInput::new(&mut self.input_state, N, &self.string_value, move |new| {
+                    match L::Target::from_input(&new) {
+                        Ok(v) => Message::Mutate(
+                            Box::new(LensSet::<L>::in_session(v, session)),
+                            Box::new(LensSet::<RootLens<_>>::in_session(None, session)),
+                        ),
+                        Err(_) => Message::Mutate(
+                            Box::new(NopMutator),
+                            Box::new(LensSet::<RootLens<_>>::in_session(Some(new), session)),
+                        ),
+                    }
+                })
+                .on_submit(Message::CommitTransaction)
+                .style(if error.is_some() {
+                    theme.text_input_error()
                 } else {
-                    Message::Mutate(
-                        Box::new(NopMutator),
-                        Box::new(LensSet::<RootLens<_>>::new(Some(new))),
-                    )
-                }
-            })
-            .style(app_state.theme().text_input())
-            .padding(5)
+                    theme.text_input()
+                })
+                .padding(5),
+                Message::Nop,
+                Message::CommitTransaction,
+            )
             .into(),
         ])
         .spacing(4)
         .padding(8)
         .align_items(Align::Center)
-        .into()]
+        .into();
+
+        match error {
+            Some(e) => vec![
+                input,
+                Row::with_children(vec![Text::new(e).color(theme.text_error()).into()])
+                    .padding(8)
+                    .into(),
+            ],
+            None => vec![input],
+        }
     }
 }
 
@@ -78,14 +114,18 @@ where
 /// For instance, when parsing floats we fail when there's a trailing . because otherwise
 /// they are immediately erased.
 pub trait FromTextInput: Sized + Display {
-    fn from_input(s: &str) -> Option<Self>;
+    /// Parse the input, or describe what's wrong with it so it can be shown to the user.
+    fn from_input(s: &str) -> Result<Self, String>;
 }
 
 impl<const N: &'static str, L> Default for TextInputField<N, L> {
     fn default() -> Self {
         Self {
             input_state: text_input::State::default(),
+            focus_state: focus::State::default(),
             string_value: "".to_string(),
+            session: Session::new(),
+            was_focused: false,
             _d: PhantomData,
         }
     }
@@ -93,14 +133,16 @@ impl<const N: &'static str, L> Default for TextInputField<N, L> {
 
 /// Implementation for anything that implements FromStr
 /// This also checks that the [`std::fmt::Display`] representation is the same as what the user inputted.
-impl<T: Display + FromStr> FromTextInput for T {
-    fn from_input(s: &str) -> Option<Self> {
-        if let Ok(x) = <Self as FromStr>::from_str(s) {
-            if format!("{}", x) == s {
-                return Some(x);
-            }
+impl<T: Display + FromStr> FromTextInput for T
+where
+    T::Err: Display,
+{
+    fn from_input(s: &str) -> Result<Self, String> {
+        let x = <Self as FromStr>::from_str(s).map_err(|e| e.to_string())?;
+        if format!("{}", x) != s {
+            return Err(format!("Would be read as {}", x));
         }
 
-        None
+        Ok(x)
     }
 }
@@ -0,0 +1,71 @@
+//! A widget which flips a bool when clicked.
+
+use iced_native::{event, mouse, Clipboard, Element, Event, Layout, Point, Widget};
+
+/// Wraps some content, usually a heading, so that clicking it flips a bool.
+/// Since that doesn't change any app state, a message is also produced so the view is rebuilt.
+pub struct Toggle<'a, Message, Renderer> {
+    value: &'a mut bool,
+    content: Element<'a, Message, Renderer>,
+    on_toggle: Message,
+}
+
+impl<'a, Message, Renderer> Toggle<'a, Message, Renderer> {
+    /// Create a toggle around `content`, which flips `value` and produces `on_toggle` when clicked
+    pub fn new(
+        value: &'a mut bool,
+        content: impl Into<Element<'a, Message, Renderer>>,
+        on_toggle: Message,
+    ) -> Self {
+        Self {
+            value,
+            content: content.into(),
+            on_toggle,
+        }
+    }
+}
+
+impl<'a, Message: Clone, Renderer: iced_native::Renderer> Widget<Message, Renderer>
+    for Toggle<'a, Message, Renderer>
+{
+    wrap_content!();
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        messages: &mut Vec<Message>,
+    ) -> event::Status {
+        match event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left))
+                if layout.bounds().contains(cursor_position) =>
+            {
+                *self.value = !*self.value;
+                messages.push(self.on_toggle.clone());
+
+                event::Status::Captured
+            }
+            _ => self.content.on_event(
+                event,
+                layout,
+                cursor_position,
+                renderer,
+                clipboard,
+                messages,
+            ),
+        }
+    }
+}
+
+impl<'a, Message, Renderer> From<Toggle<'a, Message, Renderer>> for Element<'a, Message, Renderer>
+where
+    Message: 'a + Clone,
+    Renderer: 'a + iced_native::Renderer,
+{
+    fn from(toggle: Toggle<'a, Message, Renderer>) -> Self {
+        Element::new(toggle)
+    }
+}
@@ -0,0 +1,137 @@
+use crate::{
+    app::AppState,
+    fields::{indented, lift},
+    lens::Lens,
+    message::Message,
+    mutation::{LensSet, NopMutator},
+    Field, Kind, ObjectStore,
+};
+use iced::{pick_list, Align, Column, Element, Length, PickList, Row, Subscription, Text};
+use std::{
+    fmt::{Debug, Display},
+    marker::PhantomData,
+};
+
+/// An enum which can be switched between its variants, such as by an [`EnumField`].
+/// This can be derived using `#[derive(Variants)]` from iroh-codegen.
+pub trait Variants: 'static + Sized + Clone + Debug + Send {
+    /// The name of each variant, in the order they should be listed
+    const NAMES: &'static [&'static str];
+
+    /// Which variant this is, as an index into [`Self::NAMES`]
+    fn variant(&self) -> usize;
+
+    /// A value of the given variant, with a default payload
+    fn default_variant(index: usize) -> Self;
+}
+
+/// A field for enums, with a dropdown to switch variant and the field for the active variant beneath it.
+/// `L` gets the enum, and `F` edits it. This is usually a [`crate::kinds::ConsFields`] of [`super::PrismField`]s,
+/// so that only the active variant's fields are shown, and each variant has its own working values.
+/// Switching variant replaces the payload with [`Variants::default_variant`], so values already set on the old variant
+/// are lost, though undo brings them back. Working values are left alone, so text that hadn't parsed yet survives.
+#[derive(Debug, Clone)]
+pub struct EnumField<const N: &'static str, L, F> {
+    inner: F,
+    list_state: pick_list::State<VariantChoice>,
+    _d: PhantomData<L>,
+}
+
+impl<K, L, E, F, const N: &'static str> Field<K> for EnumField<N, L, F>
+where
+    K: Kind,
+    L: 'static + Lens<Target = E>,
+    L::Source: 'static + Debug + Clone + Send,
+    E: Variants,
+    F: Field<K, Source = E>,
+{
+    type Source = L::Source;
+    type WorkingValues = F::WorkingValues;
+
+    fn view<C: ObjectStore<K>>(
+        &mut self,
+        key: &K::Key,
+        val: &L::Source,
+        app_state: &AppState<K, C>,
+        working: &F::WorkingValues,
+    ) -> Vec<Element<Message<K, Self::WorkingValues, L::Source>>> {
+        let value = L::get(val);
+        let active = value.variant();
+        let options = (0..E::NAMES.len())
+            .map(VariantChoice::new::<E>)
+            .collect::<Vec<_>>();
+
+        let mut col = Column::new().spacing(4).push(
+            Row::with_children(vec![
+                Text::new(N).color(app_state.theme().text_primary()).into(),
+                PickList::new(
+                    &mut self.list_state,
+                    options,
+                    Some(VariantChoice::new::<E>(active)),
+                    move |c| {
+                        if c.index == active {
+                            return Message::Nop;
+                        }
+
+                        Message::Mutate(
+                            Box::new(LensSet::<L>::new(E::default_variant(c.index))),
+                            Box::new(NopMutator),
+                        )
+                    },
+                )
+                .style(app_state.theme().pick_list())
+                .into(),
+            ])
+            .spacing(4)
+            .padding(8)
+            .align_items(Align::Center),
+        );
+
+        let mut inner = Column::new().spacing(4).width(Length::Fill);
+        for e in self.inner.view(key, value, app_state, working) {
+            inner = inner.push(e.map(lift::<K, L, F::WorkingValues>));
+        }
+
+        col = col.push(indented(inner));
+
+        vec![col.into()]
+    }
+
+    fn subscription(&self) -> Subscription<Message<K, Self::WorkingValues, L::Source>> {
+        self.inner
+            .subscription()
+            .map(lift::<K, L, F::WorkingValues>)
+    }
+}
+
+impl<const N: &'static str, L, F: Default> Default for EnumField<N, L, F> {
+    fn default() -> Self {
+        Self {
+            inner: F::default(),
+            list_state: pick_list::State::default(),
+            _d: PhantomData,
+        }
+    }
+}
+
+/// Shows a variant by its name, since that's what [`PickList`] displays.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VariantChoice {
+    index: usize,
+    name: &'static str,
+}
+
+impl VariantChoice {
+    fn new<E: Variants>(index: usize) -> Self {
+        Self {
+            index,
+            name: E::NAMES[index],
+        }
+    }
+}
+
+impl Display for VariantChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
@@ -0,0 +1,402 @@
+use crate::{
+    app::AppState,
+    fields::{lift, INDENT},
+    lens::Lens,
+    message::Message,
+    mutation::{IndexMutation, InnerMutation, Mutator, Session, VecInsert, VecRemove, VecSwap},
+    Field, Kind, ObjectStore,
+};
+use iced::{button, Align, Button, Column, Element, Length, Row, Space, Subscription, Text};
+use std::{
+    fmt::Debug,
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+/// A field for a Vec, with a sub-field for each element, and controls to add, remove and reorder them.
+/// `L` gets the Vec, and `F` is the field for one element.
+/// Each element has its own working values and UI state, which move along with it, including when it's undone.
+#[derive(Debug, Clone)]
+pub struct VecField<const N: &'static str, L, F> {
+    elements: Vec<ElementState<F>>,
+    removed: Vec<ElementState<F>>,
+    add_state: button::State,
+    _d: PhantomData<L>,
+}
+
+/// Identifies an element, so its UI state can find its working values after they've moved.
+/// New IDs are unique, so each element's UI state starts with its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct ElementId(u64);
+
+impl Default for ElementId {
+    fn default() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+
+        Self(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// The working values for one element of a [`VecField`]
+#[derive(Debug, Clone, Default)]
+pub struct ElementWorkingValues<W> {
+    /// The element whose UI state goes with these
+    id: ElementId,
+    values: W,
+}
+
+/// Gets the working values of the element's own field
+#[derive(Debug, Clone)]
+struct ValuesLens<W>(PhantomData<W>);
+impl<W: Debug + Clone + Send> Lens for ValuesLens<W> {
+    type Source = ElementWorkingValues<W>;
+    type Target = W;
+
+    fn get(source: &Self::Source) -> &Self::Target {
+        &source.values
+    }
+
+    fn get_mut(source: &mut Self::Source) -> &mut Self::Target {
+        &mut source.values
+    }
+}
+
+/// The sub-field and controls for one element
+#[derive(Debug, Clone, Default)]
+struct ElementState<F> {
+    id: ElementId,
+    field: F,
+    up_state: button::State,
+    down_state: button::State,
+    remove_state: button::State,
+}
+
+impl<K, L, T, F, const N: &'static str> Field<K> for VecField<N, L, F>
+where
+    K: Kind,
+    L: 'static + Lens<Target = Vec<T>>,
+    L::Source: 'static + Debug + Clone + Send,
+    T: 'static + Default + Debug + Clone + Send,
+    F: Field<K, Source = T>,
+{
+    type Source = L::Source;
+    type WorkingValues = Vec<ElementWorkingValues<F::WorkingValues>>;
+
+    fn view<C: ObjectStore<K>>(
+        &mut self,
+        key: &K::Key,
+        val: &L::Source,
+        app_state: &AppState<K, C>,
+        working: &Self::WorkingValues,
+    ) -> Vec<Element<Message<K, Self::WorkingValues, L::Source>>> {
+        let values = L::get(val);
+        let len = values.len();
+
+        let ids = working.iter().map(|w| w.id).collect::<Vec<_>>();
+        follow(&mut self.elements, &mut self.removed, &ids, len);
+        let ids = self.elements.iter().map(|e| e.id).collect::<Arc<[_]>>();
+
+        let theme = app_state.theme();
+        let mut col = Column::new().spacing(4).push(
+            Row::new()
+                .padding(8)
+                .spacing(4)
+                .align_items(Align::Center)
+                .push(Text::new(N).color(theme.text_accent()))
+                .push(Space::with_width(Length::Fill))
+                .push(
+                    Button::new(&mut self.add_state, Text::new("+"))
+                        .on_press(structural::<K, L, T, F::WorkingValues>(
+                            ids.clone(),
+                            Box::new(VecInsert::new(len, T::default())),
+                            Box::new(VecInsert::new(len, ElementWorkingValues::default())),
+                        ))
+                        .style(theme.button_subtle()),
+                ),
+        );
+
+        // Elements without working values yet get the defaults
+        let empty = F::WorkingValues::default();
+        for (i, (value, state)) in values.iter().zip(self.elements.iter_mut()).enumerate() {
+            let mut inner = Column::new().spacing(4).width(Length::Fill);
+            let working = working.get(i).map_or(&empty, |w| &w.values);
+            for e in state.field.view(key, value, app_state, working) {
+                let ids = ids.clone();
+                inner = inner.push(
+                    e.map(move |m| lift_index::<K, L, T, F::WorkingValues>(i, ids.clone(), m)),
+                );
+            }
+
+            let mut up =
+                Button::new(&mut state.up_state, Text::new("Up")).style(theme.button_subtle());
+            let mut down =
+                Button::new(&mut state.down_state, Text::new("Down")).style(theme.button_subtle());
+            if i > 0 {
+                up = up.on_press(structural::<K, L, T, F::WorkingValues>(
+                    ids.clone(),
+                    Box::new(VecSwap::new(i - 1, i)),
+                    Box::new(VecSwap::new(i - 1, i)),
+                ));
+            }
+            if i + 1 < len {
+                down = down.on_press(structural::<K, L, T, F::WorkingValues>(
+                    ids.clone(),
+                    Box::new(VecSwap::new(i, i + 1)),
+                    Box::new(VecSwap::new(i, i + 1)),
+                ));
+            }
+            let remove = Button::new(&mut state.remove_state, Text::new("X"))
+                .on_press(structural::<K, L, T, F::WorkingValues>(
+                    ids.clone(),
+                    Box::new(VecRemove::<T>::new(i)),
+                    Box::new(VecRemove::<ElementWorkingValues<F::WorkingValues>>::new(i)),
+                ))
+                .style(theme.button_subtle());
+
+            col = col.push(
+                Row::new()
+                    .spacing(4)
+                    .align_items(Align::Center)
+                    .push(Space::with_width(Length::Units(INDENT)))
+                    .push(inner)
+                    .push(up)
+                    .push(down)
+                    .push(remove),
+            );
+        }
+
+        vec![col.into()]
+    }
+
+    fn subscription(&self) -> Subscription<Message<K, Self::WorkingValues, L::Source>> {
+        let ids = self.elements.iter().map(|e| e.id).collect::<Arc<[_]>>();
+        Subscription::batch(
+            self.elements
+                .iter()
+                .enumerate()
+                .map(|(i, e)| {
+                    e.field
+                        .subscription()
+                        .with((i, ids.clone()))
+                        .map(|((i, ids), m)| lift_index::<K, L, T, F::WorkingValues>(i, ids, m))
+                })
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+
+impl<const N: &'static str, L, F: Default> Default for VecField<N, L, F> {
+    fn default() -> Self {
+        Self {
+            elements: vec![],
+            removed: vec![],
+            add_state: button::State::default(),
+            _d: PhantomData,
+        }
+    }
+}
+
+/// Line the UI state for each element up with its working values, which undo and redo move along with the values.
+/// Elements with no working values yet keep the UI state at their position. UI state that's no longer in use is kept
+/// in `removed`, so undoing the removal of an element brings it back.
+fn follow<F: Default>(
+    elements: &mut Vec<ElementState<F>>,
+    removed: &mut Vec<ElementState<F>>,
+    ids: &[ElementId],
+    len: usize,
+) {
+    if elements.len() == len && elements.iter().zip(ids).all(|(e, id)| e.id == *id) {
+        return;
+    }
+
+    let mut old = elements.drain(..).map(Some).collect::<Vec<_>>();
+    for i in 0..len {
+        let state = match ids.get(i) {
+            Some(&id) => old
+                .iter_mut()
+                .find(|e| matches!(e, Some(e) if e.id == id))
+                .and_then(Option::take)
+                .or_else(|| {
+                    let j = removed.iter().position(|e| e.id == id)?;
+                    Some(removed.swap_remove(j))
+                })
+                .unwrap_or_else(|| ElementState {
+                    id,
+                    ..ElementState::default()
+                }),
+            None => old.get_mut(i).and_then(Option::take).unwrap_or_default(),
+        };
+        elements.push(state);
+    }
+    removed.extend(old.into_iter().flatten());
+}
+
+/// Lift a message from the field for element `i` to one for the whole Vec
+fn lift_index<K, L, T, W>(
+    i: usize,
+    ids: Arc<[ElementId]>,
+    m: Message<K, W, T>,
+) -> Message<K, Vec<ElementWorkingValues<W>>, L::Source>
+where
+    K: Kind,
+    L: 'static + Lens<Target = Vec<T>>,
+    L::Source: 'static,
+    T: 'static + Debug + Clone + Send,
+    W: 'static + Default + Debug + Clone + Send,
+{
+    lift::<K, L, Vec<ElementWorkingValues<W>>>(m.map(
+        |v| Box::new(IndexMutation::new(i, v)),
+        |w| {
+            Box::new(Padded::new(
+                ids,
+                Box::new(IndexMutation::new(
+                    i,
+                    Box::new(InnerMutation::<ValuesLens<W>>::new(w)),
+                )),
+            ))
+        },
+    ))
+}
+
+/// A message which changes the structure of the Vec, applying the same change to the values and working values
+fn structural<K, L, T, W>(
+    ids: Arc<[ElementId]>,
+    value: Box<dyn Mutator<Vec<T>>>,
+    working: Box<dyn Mutator<Vec<ElementWorkingValues<W>>>>,
+) -> Message<K, Vec<ElementWorkingValues<W>>, L::Source>
+where
+    K: Kind,
+    L: 'static + Lens<Target = Vec<T>>,
+    L::Source: 'static,
+    T: 'static + Debug + Clone + Send,
+    W: 'static + Default + Debug + Clone + Send,
+{
+    lift::<K, L, Vec<ElementWorkingValues<W>>>(Message::Mutate(
+        value,
+        Box::new(Padded::new(ids, working)),
+    ))
+}
+
+/// Gives every element working values, tagged with the IDs of their UI state, then applies another mutator.
+/// Objects start with no working values for any element, so this keeps them lined up with the values.
+#[derive(Debug, Clone)]
+struct Padded<W>(
+    Arc<[ElementId]>,
+    Box<dyn Mutator<Vec<ElementWorkingValues<W>>>>,
+);
+impl<W> Padded<W> {
+    fn new(ids: Arc<[ElementId]>, m: Box<dyn Mutator<Vec<ElementWorkingValues<W>>>>) -> Self {
+        Self(ids, m)
+    }
+}
+impl<W: 'static + Default + Debug + Clone + Send> Mutator<Vec<ElementWorkingValues<W>>>
+    for Padded<W>
+{
+    fn apply(
+        self: Box<Self>,
+        target: &mut Vec<ElementWorkingValues<W>>,
+    ) -> Box<dyn Mutator<Vec<ElementWorkingValues<W>>>> {
+        if target.len() < self.0.len() {
+            let ids = self.0[target.len()..].iter();
+            target.extend(ids.map(|id| ElementWorkingValues {
+                id: *id,
+                values: W::default(),
+            }));
+        }
+
+        self.1.apply(target)
+    }
+
+    fn session(&self) -> Option<Session> {
+        self.1.session()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn states(fields: &str) -> Vec<ElementState<char>> {
+        fields
+            .chars()
+            .map(|field| ElementState {
+                field,
+                ..ElementState::default()
+            })
+            .collect()
+    }
+
+    fn fields(elements: &[ElementState<char>]) -> String {
+        elements.iter().map(|e| e.field).collect()
+    }
+
+    fn ids<W>(working: &[ElementWorkingValues<W>]) -> Vec<ElementId> {
+        working.iter().map(|w| w.id).collect()
+    }
+
+    #[test]
+    fn follow_ids() {
+        let mut elements = states("abc");
+        let mut removed = vec![];
+        let [a, b, c] = [elements[0].id, elements[1].id, elements[2].id];
+
+        follow(&mut elements, &mut removed, &[b, a, c], 3);
+        assert_eq!(fields(&elements), "bac");
+
+        follow(&mut elements, &mut removed, &[b, c], 2);
+        assert_eq!(fields(&elements), "bc");
+        assert_eq!(fields(&removed), "a");
+
+        follow(&mut elements, &mut removed, &[a, b, c], 3);
+        assert_eq!(fields(&elements), "abc");
+        assert!(removed.is_empty());
+    }
+
+    #[test]
+    fn follow_without_working_values() {
+        let mut elements = states("abc");
+        let mut removed = vec![];
+        let a = elements[0].id;
+
+        follow(&mut elements, &mut removed, &[], 4);
+        assert_eq!(fields(&elements), "abc\0");
+
+        follow(&mut elements, &mut removed, &[a], 2);
+        assert_eq!(fields(&elements), "ab");
+        assert_eq!(fields(&removed), "c\0");
+    }
+
+    #[test]
+    fn follow_new_id() {
+        let mut elements = states("a");
+        let mut removed = vec![];
+        let [a, new] = [elements[0].id, ElementId::default()];
+
+        follow(&mut elements, &mut removed, &[a, new], 2);
+        assert_eq!(elements[1].id, new);
+    }
+
+    #[test]
+    fn undo_remove() {
+        let mut elements = states("abc");
+        let mut removed = vec![];
+        let mut working = vec![];
+        let all = elements.iter().map(|e| e.id).collect::<Arc<[_]>>();
+
+        let remove = Box::new(Padded::<()>::new(all, Box::new(VecRemove::new(1))));
+        let undo = remove.apply(&mut working);
+        follow(&mut elements, &mut removed, &ids(&working), 2);
+        assert_eq!(fields(&elements), "ac");
+
+        let redo = undo.apply(&mut working);
+        follow(&mut elements, &mut removed, &ids(&working), 3);
+        assert_eq!(fields(&elements), "abc");
+
+        redo.apply(&mut working);
+        follow(&mut elements, &mut removed, &ids(&working), 2);
+        assert_eq!(fields(&elements), "ac");
+    }
+}
@@ -0,0 +1,471 @@
+//! Keeps track of applied mutations so they can be undone and redone.
+
+use crate::{
+    mutation::{Mutator, Session},
+    Field, Kind, ObjectStore,
+};
+
+/// The mutators which reverse an edit to one object.
+struct Step<K: Kind> {
+    key: K::Key,
+    value: Box<dyn Mutator<K>>,
+    working: Box<dyn Mutator<<<K as Kind>::Field as Field<K>>::WorkingValues>>,
+}
+
+/// One undoable step, made of one or more edits.
+struct Entry<K: Kind> {
+    /// In the order they were applied
+    steps: Vec<Step<K>>,
+
+    /// If set, later edits to this object in this session are merged into this entry.
+    session: Option<(K::Key, Session)>,
+}
+
+/// Undo & redo stacks for the objects in a store.
+pub struct History<K: Kind> {
+    undo: Vec<Entry<K>>,
+    redo: Vec<Entry<K>>,
+
+    /// How many transactions are open. While this is above 0, all edits go into the top entry.
+    depth: usize,
+
+    /// Whether the top entry can still have edits merged into it.
+    mergeable: bool,
+}
+
+impl<K: Kind> History<K> {
+    /// Create an empty history
+    pub fn new() -> Self {
+        Self {
+            undo: vec![],
+            redo: vec![],
+            depth: 0,
+            mergeable: false,
+        }
+    }
+
+    /// Apply the given mutations to the object with the given key, recording how to undo them.
+    /// Returns false if there's no such object.
+    pub fn apply<C: ObjectStore<K>>(
+        &mut self,
+        container: &mut C,
+        key: &K::Key,
+        value: Box<dyn Mutator<K>>,
+        working: Box<dyn Mutator<<<K as Kind>::Field as Field<K>>::WorkingValues>>,
+    ) -> bool {
+        let session = value
+            .session()
+            .or_else(|| working.session())
+            .map(|s| (key.clone(), s));
+
+        let step = match container.get_mut(key) {
+            Some((v, w)) => Step {
+                key: key.clone(),
+                value: value.apply(v),
+                working: working.apply(w),
+            },
+            None => return false,
+        };
+        self.redo.clear();
+
+        match self.undo.last_mut() {
+            Some(top) if self.depth > 0 => top.steps.push(step),
+            Some(top) if self.mergeable && session.is_some() && top.session == session => {
+                top.steps.push(step)
+            }
+            _ => {
+                self.undo.push(Entry {
+                    steps: vec![step],
+                    session,
+                });
+                self.mergeable = true;
+            }
+        }
+
+        true
+    }
+
+    /// Start a transaction. Every edit until the matching [`Self::commit`] is undone as one step.
+    /// Transactions can be nested, in which case only the outermost one matters.
+    pub fn begin(&mut self) {
+        if self.depth == 0 {
+            self.undo.push(Entry {
+                steps: vec![],
+                session: None,
+            });
+        }
+
+        self.depth += 1;
+    }
+
+    /// Finish the current transaction, if there is one.
+    /// Either way, the last edit won't have any more edits merged into it.
+    pub fn commit(&mut self) {
+        if self.depth > 0 {
+            self.depth -= 1;
+
+            // Don't leave an empty step around
+            if self.depth == 0 && matches!(self.undo.last(), Some(e) if e.steps.is_empty()) {
+                self.undo.pop();
+            }
+        }
+
+        self.mergeable = false;
+    }
+
+    /// Undo the last step, returning the key of the object it changed.
+    pub fn undo<C: ObjectStore<K>>(&mut self, container: &mut C) -> Option<K::Key> {
+        self.close();
+        Self::step(&mut self.undo, &mut self.redo, container)
+    }
+
+    /// Redo the last undone step, returning the key of the object it changed.
+    pub fn redo<C: ObjectStore<K>>(&mut self, container: &mut C) -> Option<K::Key> {
+        self.close();
+        Self::step(&mut self.redo, &mut self.undo, container)
+    }
+
+    /// Forget every edit to the given object, such as when it's deleted.
+    /// Open transactions stay open, so their callers can still commit them.
+    pub fn forget(&mut self, key: &K::Key) {
+        for stack in [&mut self.undo, &mut self.redo].iter_mut() {
+            for entry in stack.iter_mut() {
+                entry.steps.retain(|s| &s.key != key);
+            }
+        }
+
+        // An open transaction's entry is kept even if it's now empty, since edits still go into it.
+        // It's dropped on commit if nothing else is added.
+        let open = if self.depth > 0 {
+            self.undo.pop()
+        } else {
+            None
+        };
+        for stack in [&mut self.undo, &mut self.redo].iter_mut() {
+            stack.retain(|e| !e.steps.is_empty());
+        }
+        self.undo.extend(open);
+
+        self.mergeable = false;
+    }
+
+    /// Forget everything, such as when a different document is opened.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.depth = 0;
+        self.mergeable = false;
+    }
+
+    /// Commit any open transactions.
+    fn close(&mut self) {
+        while self.depth > 0 {
+            self.commit();
+        }
+        self.mergeable = false;
+    }
+
+    /// Pop an entry from `from` and apply it, pushing its inverse onto `to`.
+    /// Edits to objects that no longer exist are skipped, as are entries with nothing left to apply.
+    fn step<C: ObjectStore<K>>(
+        from: &mut Vec<Entry<K>>,
+        to: &mut Vec<Entry<K>>,
+        container: &mut C,
+    ) -> Option<K::Key> {
+        while let Some(entry) = from.pop() {
+            // Steps are undone in the reverse order they were applied.
+            let mut inverse = Vec::with_capacity(entry.steps.len());
+            for Step {
+                key,
+                value,
+                working,
+            } in entry.steps.into_iter().rev()
+            {
+                if let Some((v, w)) = container.get_mut(&key) {
+                    inverse.push(Step {
+                        value: value.apply(v),
+                        working: working.apply(w),
+                        key,
+                    });
+                }
+            }
+
+            if let Some(last) = inverse.last() {
+                let key = last.key.clone();
+                to.push(Entry {
+                    steps: inverse,
+                    session: None,
+                });
+
+                return Some(key);
+            }
+        }
+
+        None
+    }
+}
+
+impl<K: Kind> Default for History<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        lens::RootLens,
+        mutation::{LensSet, NopMutator, Session},
+        stores::VecContainer,
+        testing::{Thing, ThingNameLens},
+    };
+
+    fn rename(name: &str) -> Box<dyn Mutator<Thing>> {
+        Box::new(LensSet::<ThingNameLens>::new(name.to_string()))
+    }
+
+    fn rename_in(name: &str, session: Session) -> Box<dyn Mutator<Thing>> {
+        Box::new(LensSet::<ThingNameLens>::in_session(
+            name.to_string(),
+            session,
+        ))
+    }
+
+    fn name(store: &VecContainer<Thing>, key: u32) -> &str {
+        &store.get(&key).unwrap().0.name
+    }
+
+    #[test]
+    fn undo_redo() {
+        let mut store = VecContainer::<Thing>::empty();
+        let key = *store.insert_with(Thing::new("a", 0));
+        let mut history = History::new();
+
+        assert!(history.apply(&mut store, &key, rename("b"), Box::new(NopMutator)));
+        assert!(history.apply(&mut store, &key, rename("c"), Box::new(NopMutator)));
+        assert_eq!("c", name(&store, key));
+
+        assert_eq!(Some(key), history.undo(&mut store));
+        assert_eq!("b", name(&store, key));
+        assert_eq!(Some(key), history.undo(&mut store));
+        assert_eq!("a", name(&store, key));
+        assert_eq!(None, history.undo(&mut store));
+        assert_eq!("a", name(&store, key));
+
+        assert_eq!(Some(key), history.redo(&mut store));
+        assert_eq!("b", name(&store, key));
+        assert_eq!(Some(key), history.redo(&mut store));
+        assert_eq!("c", name(&store, key));
+        assert_eq!(None, history.redo(&mut store));
+    }
+
+    #[test]
+    fn undo_working_values() {
+        let mut store = VecContainer::<Thing>::empty();
+        let key = *store.insert_with(Thing::default());
+        let mut history = History::new();
+
+        history.apply(
+            &mut store,
+            &key,
+            Box::new(NopMutator),
+            Box::new(LensSet::<RootLens<_>>::new(Some("1.".to_string()))),
+        );
+        assert_eq!(&Some("1.".to_string()), store.get(&key).unwrap().1);
+
+        history.undo(&mut store);
+        assert_eq!(&None, store.get(&key).unwrap().1);
+    }
+
+    #[test]
+    fn new_edit_clears_redo() {
+        let mut store = VecContainer::<Thing>::empty();
+        let key = *store.insert_with(Thing::new("a", 0));
+        let mut history = History::new();
+
+        history.apply(&mut store, &key, rename("b"), Box::new(NopMutator));
+        history.undo(&mut store);
+        history.apply(&mut store, &key, rename("c"), Box::new(NopMutator));
+
+        assert_eq!(None, history.redo(&mut store));
+        assert_eq!("c", name(&store, key));
+    }
+
+    #[test]
+    fn missing_object() {
+        let mut store = VecContainer::<Thing>::empty();
+        let mut history = History::new();
+
+        assert!(!history.apply(&mut store, &0, rename("b"), Box::new(NopMutator)));
+        assert_eq!(None, history.undo(&mut store));
+    }
+
+    #[test]
+    fn merge_session() {
+        let mut store = VecContainer::<Thing>::empty();
+        let key = *store.insert_with(Thing::new("", 0));
+        let mut history = History::new();
+
+        let typing = Session::new();
+        for name in ["a", "ab", "abc"].iter() {
+            history.apply(
+                &mut store,
+                &key,
+                rename_in(name, typing),
+                Box::new(NopMutator),
+            );
+        }
+
+        // A different session starts a new step
+        history.apply(
+            &mut store,
+            &key,
+            rename_in("x", Session::new()),
+            Box::new(NopMutator),
+        );
+
+        history.undo(&mut store);
+        assert_eq!("abc", name(&store, key));
+        history.undo(&mut store);
+        assert_eq!("", name(&store, key));
+        history.redo(&mut store);
+        assert_eq!("abc", name(&store, key));
+    }
+
+    #[test]
+    fn commit_ends_session() {
+        let mut store = VecContainer::<Thing>::empty();
+        let key = *store.insert_with(Thing::new("", 0));
+        let mut history = History::new();
+
+        let typing = Session::new();
+        history.apply(
+            &mut store,
+            &key,
+            rename_in("a", typing),
+            Box::new(NopMutator),
+        );
+        history.commit();
+        history.apply(
+            &mut store,
+            &key,
+            rename_in("ab", typing),
+            Box::new(NopMutator),
+        );
+
+        history.undo(&mut store);
+        assert_eq!("a", name(&store, key));
+    }
+
+    #[test]
+    fn session_per_object() {
+        let mut store = VecContainer::<Thing>::empty();
+        let a = *store.insert_with(Thing::new("", 0));
+        let b = *store.insert_with(Thing::new("", 0));
+        let mut history = History::new();
+
+        let typing = Session::new();
+        history.apply(&mut store, &a, rename_in("a", typing), Box::new(NopMutator));
+        history.apply(&mut store, &b, rename_in("b", typing), Box::new(NopMutator));
+
+        assert_eq!(Some(b), history.undo(&mut store));
+        assert_eq!("a", name(&store, a));
+    }
+
+    #[test]
+    fn nested_transactions() {
+        let mut store = VecContainer::<Thing>::empty();
+        let a = *store.insert_with(Thing::new("", 0));
+        let b = *store.insert_with(Thing::new("", 0));
+        let mut history = History::new();
+
+        history.begin();
+        history.apply(&mut store, &a, rename("a"), Box::new(NopMutator));
+        history.begin();
+        history.apply(&mut store, &b, rename("b"), Box::new(NopMutator));
+        history.commit();
+        history.apply(&mut store, &a, rename("aa"), Box::new(NopMutator));
+        history.commit();
+        history.apply(&mut store, &a, rename("aaa"), Box::new(NopMutator));
+
+        history.undo(&mut store);
+        assert_eq!("aa", name(&store, a));
+
+        // Everything inside the outer transaction is undone at once, in reverse order
+        assert_eq!(Some(a), history.undo(&mut store));
+        assert_eq!("", name(&store, a));
+        assert_eq!("", name(&store, b));
+        assert_eq!(None, history.undo(&mut store));
+
+        history.redo(&mut store);
+        assert_eq!("aa", name(&store, a));
+        assert_eq!("b", name(&store, b));
+    }
+
+    #[test]
+    fn empty_transaction() {
+        let mut store = VecContainer::<Thing>::empty();
+        let key = *store.insert_with(Thing::new("a", 0));
+        let mut history = History::new();
+
+        history.apply(&mut store, &key, rename("b"), Box::new(NopMutator));
+        history.begin();
+        history.commit();
+
+        history.undo(&mut store);
+        assert_eq!("a", name(&store, key));
+    }
+
+    #[test]
+    fn undo_closes_transaction() {
+        let mut store = VecContainer::<Thing>::empty();
+        let key = *store.insert_with(Thing::new("a", 0));
+        let mut history = History::new();
+
+        history.begin();
+        history.apply(&mut store, &key, rename("b"), Box::new(NopMutator));
+        history.undo(&mut store);
+        history.apply(&mut store, &key, rename("c"), Box::new(NopMutator));
+
+        history.undo(&mut store);
+        assert_eq!("a", name(&store, key));
+    }
+
+    #[test]
+    fn forget() {
+        let mut store = VecContainer::<Thing>::empty();
+        let a = *store.insert_with(Thing::new("a", 0));
+        let b = *store.insert_with(Thing::new("b", 0));
+        let mut history = History::new();
+
+        history.apply(&mut store, &a, rename("a2"), Box::new(NopMutator));
+        history.apply(&mut store, &b, rename("b2"), Box::new(NopMutator));
+        history.apply(&mut store, &a, rename("a3"), Box::new(NopMutator));
+        history.forget(&a);
+
+        assert_eq!(Some(b), history.undo(&mut store));
+        assert_eq!("b", name(&store, b));
+        assert_eq!("a3", name(&store, a));
+        assert_eq!(None, history.undo(&mut store));
+    }
+
+    #[test]
+    fn forget_keeps_transaction_open() {
+        let mut store = VecContainer::<Thing>::empty();
+        let a = *store.insert_with(Thing::new("a", 0));
+        let b = *store.insert_with(Thing::new("b", 0));
+        let mut history = History::new();
+
+        history.begin();
+        history.apply(&mut store, &a, rename("a2"), Box::new(NopMutator));
+        history.forget(&a);
+        history.apply(&mut store, &b, rename("b2"), Box::new(NopMutator));
+        history.apply(&mut store, &b, rename("b3"), Box::new(NopMutator));
+        history.commit();
+
+        // Both edits to b were still in the transaction
+        history.undo(&mut store);
+        assert_eq!("b", name(&store, b));
+    }
+}
@@ -6,67 +6,76 @@ use crate::{
     mutation::InnerMutation,
     Message, ObjectStore,
 };
-use iced::Element;
+use iced::{Element, Subscription};
 use std::fmt::Debug;
 
 /// A type of object contained by a [`ObjectStore`]
 pub trait Kind: 'static + Clone + Debug + Default {
     type Key: Key;
-    type Field: Field<Kind = Self>;
+    type Field: Field<Self, Source = Self>;
 }
 
-/// A single editable part of a Kind
-pub trait Field: Default + Clone + Debug {
-    type Kind: Kind;
+/// A single editable part of an object of kind `K`.
+/// Fields edit a `Source` value, which is the whole object unless the field is nested inside another one.
+pub trait Field<K: Kind>: Default + Clone + Debug {
+    type Source: 'static + Debug + Clone + Send;
     type WorkingValues: 'static + Default + Debug + Clone + Send;
 
-    fn view<C: ObjectStore<Self::Kind>>(
+    fn view<C: ObjectStore<K>>(
         &mut self,
-        key: &<<Self as Field>::Kind as Kind>::Key,
-        val: &Self::Kind,
-        app_state: &AppState<Self::Kind, C>,
-        working: &<Self as Field>::WorkingValues,
-    ) -> Vec<Element<Message<Self::Kind, Self::WorkingValues>>>;
+        key: &K::Key,
+        val: &Self::Source,
+        app_state: &AppState<K, C>,
+        working: &Self::WorkingValues,
+    ) -> Vec<Element<FieldMessage<K, Self>>>;
+
+    /// Events this field wants to hear about while it's on screen, such as timers or keyboard input.
+    fn subscription(&self) -> Subscription<FieldMessage<K, Self>> {
+        Subscription::none()
+    }
 }
 
+/// A message from a field `F` of an object of kind `K`, which mutates the field's source and working values.
+pub type FieldMessage<K, F> = Message<K, <F as Field<K>>::WorkingValues, <F as Field<K>>::Source>;
+
 /// One field, then the other.
 #[derive(Debug, Clone)]
 pub struct ConsFields<A, B>(A, B);
-impl<K: Kind, A: Field<Kind = K>, B: Field<Kind = K>> Default for ConsFields<A, B> {
+impl<A: Default, B: Default> Default for ConsFields<A, B> {
     fn default() -> Self {
         Self(Default::default(), Default::default())
     }
 }
-impl<K: Kind, A: Field<Kind = K>, B: Field<Kind = K>> Field for ConsFields<A, B> {
-    type Kind = K;
+impl<K, S, A, B> Field<K> for ConsFields<A, B>
+where
+    K: Kind,
+    S: 'static + Debug + Clone + Send,
+    A: Field<K, Source = S>,
+    B: Field<K, Source = S>,
+{
+    type Source = S;
     type WorkingValues = (A::WorkingValues, B::WorkingValues);
 
-    fn view<C: ObjectStore<Self::Kind>>(
+    fn view<C: ObjectStore<K>>(
         &mut self,
-        key: &<<Self as Field>::Kind as Kind>::Key,
-        val: &Self::Kind,
-        app_state: &AppState<Self::Kind, C>,
-        working: &<Self as Field>::WorkingValues,
-    ) -> Vec<Element<Message<Self::Kind, Self::WorkingValues>>> {
+        key: &K::Key,
+        val: &S,
+        app_state: &AppState<K, C>,
+        working: &Self::WorkingValues,
+    ) -> Vec<Element<Message<K, Self::WorkingValues, S>>> {
         // Because a returns a message with working values A::WorkingValues, and b returns one with B::WorkingValues,
         // we need to map each one to our WorkingValues.
-        // This is why we have a lot of big match arms. Technically we could `transmute`, but that's nasty.
         let a = self
             .0
             .view(key, val, app_state, &working.0)
             .into_iter()
             .map(|x| {
-                x.map(|m| match m {
-                    Message::Mutate(v, w) => Message::Mutate(
-                        v,
+                x.map(|m| {
+                    m.map_working(|w| {
                         Box::new(InnerMutation::<
                             TupleHeadLens<A::WorkingValues, B::WorkingValues>,
-                        >::new(w)),
-                    ),
-                    Message::PaneMessage(m) => Message::PaneMessage(m),
-                    Message::Select(s) => Message::Select(s),
-                    Message::NewObject => Message::NewObject,
-                    Message::Nop => Message::Nop,
+                        >::new(w))
+                    })
                 })
             });
         let b = self
@@ -74,28 +83,46 @@ impl<K: Kind, A: Field<Kind = K>, B: Field<Kind = K>> Field for ConsFields<A, B>
             .view(key, val, app_state, &working.1)
             .into_iter()
             .map(|x| {
-                x.map(|m| match m {
-                    Message::Mutate(v, w) => Message::Mutate(
-                        v,
+                x.map(|m| {
+                    m.map_working(|w| {
                         Box::new(InnerMutation::<
                             TupleTailLens<A::WorkingValues, B::WorkingValues>,
-                        >::new(w)),
-                    ),
-                    Message::PaneMessage(m) => Message::PaneMessage(m),
-                    Message::Select(s) => Message::Select(s),
-                    Message::NewObject => Message::NewObject,
-                    Message::Nop => Message::Nop,
+                        >::new(w))
+                    })
                 })
             });
 
         a.chain(b).collect()
     }
+
+    fn subscription(&self) -> Subscription<Message<K, Self::WorkingValues, S>> {
+        Subscription::batch(vec![
+            self.0.subscription().map(|m| {
+                m.map_working(|w| {
+                    Box::new(InnerMutation::<
+                        TupleHeadLens<A::WorkingValues, B::WorkingValues>,
+                    >::new(w))
+                })
+            }),
+            self.1.subscription().map(|m| {
+                m.map_working(|w| {
+                    Box::new(InnerMutation::<
+                        TupleTailLens<A::WorkingValues, B::WorkingValues>,
+                    >::new(w))
+                })
+            }),
+        ])
+    }
 }
 
 /// Uniquely identifies a Kind in a store, and provides a way of generating keys for new objects.
 /// These keys can be randomly generated, but see the documentation for `.next()` for the invariants
 /// you need to satisfy.
-pub trait Key: 'static + Clone + Debug + Send + Sync + PartialEq {
+///
+/// Stores remember the last key they allocated and pass it to `.next()`, so finding a new key doesn't need a scan
+/// of every key in use. Keys are saved in documents along with their objects, so they stay the same when a document
+/// is opened again.
+pub trait Key: 'static + Clone + Debug + Send + Sync + Eq {
     /// Get the first key to use.
     fn first() -> Self;
 
@@ -105,3 +132,34 @@ pub trait Key: 'static + Clone + Debug + Send + Sync + PartialEq {
     /// For numbers, this can be as simple as incrementing.
     fn next(last: &Self) -> Self;
 }
+
+/// Integers count up from 0.
+macro_rules! int_key {
+    ($($t:ty),*) => {
+        $(
+            impl Key for $t {
+                fn first() -> Self {
+                    0
+                }
+
+                fn next(last: &Self) -> Self {
+                    last.checked_add(1).expect("ran out of keys")
+                }
+            }
+        )*
+    };
+}
+
+int_key!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// Random UUIDs, which won't collide with each other or with keys from other documents.
+#[cfg(feature = "uuid")]
+impl Key for uuid::Uuid {
+    fn first() -> Self {
+        uuid::Uuid::new_v4()
+    }
+
+    fn next(_last: &Self) -> Self {
+        uuid::Uuid::new_v4()
+    }
+}
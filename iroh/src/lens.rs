@@ -16,6 +16,22 @@ pub trait Lens: Debug + Send + Clone {
     fn get_mut<'a>(source: &'a mut Self::Source) -> &'a mut Self::Target;
 }
 
+/// Like a [`Lens`], but for something that might not be there, such as a field of one variant of an enum.
+/// Prisms for each variant's fields can be generated with `#[derive(Prism)]` from iroh-codegen.
+pub trait Prism: Debug + Send + Clone {
+    /// The 'root' object this accesses
+    type Source;
+
+    /// The type it borrows
+    type Target;
+
+    /// Get an immutable borrow of the targeted attribute, if it's there
+    fn get<'a>(source: &'a Self::Source) -> Option<&'a Self::Target>;
+
+    /// Get a mutable borrow of the targeted attribute, if it's there
+    fn get_mut<'a>(source: &'a mut Self::Source) -> Option<&'a mut Self::Target>;
+}
+
 /// Applies lens A, then lens B.
 #[derive(Debug, Clone)]
 pub struct CompositeLens<A: Lens, B: Lens>(PhantomData<(A, B)>);
@@ -2,18 +2,24 @@
 #![feature(generic_associated_types, const_generics)]
 
 pub mod app;
+pub mod codec;
 pub mod fields;
+mod history;
 pub mod kinds;
 pub mod lens;
 pub mod message;
 pub mod mutation;
 mod pane_zone;
-mod panes;
+pub mod panes;
 pub mod stores;
+#[cfg(test)]
+mod testing;
 mod theme;
+mod toolbar;
 
-pub use app::App;
-pub use iced::{Sandbox, Settings};
+pub use app::{App, AppBuilder};
+pub use codec::DocumentCodec;
+pub use iced::{Application, Settings};
 pub use kinds::{Field, Key, Kind};
 pub use message::Message;
 pub use stores::ObjectStore;
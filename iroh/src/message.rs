@@ -1,7 +1,8 @@
 //! The message types used throughout the application
 
 use crate::{mutation::Mutator, Field, Kind};
-use iced::pane_grid;
+use iced::{pane_grid, Command};
+use std::{fmt::Debug, path::PathBuf, sync::Arc};
 
 /// A message related to a pane zone.
 #[derive(Debug, Clone, Copy)]
@@ -13,12 +14,9 @@ pub enum PaneMessage {
     Set(pane_grid::Pane, NewPane),
 }
 
-/// Which pane to open
-#[derive(Debug, Clone, Copy)]
-pub enum NewPane {
-    Outline,
-    Inspector,
-}
+/// Which pane to open, by the name it was registered with in the [`crate::panes::PaneRegistry`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NewPane(pub &'static str);
 
 impl<K: Kind> Into<Message<K>> for PaneMessage {
     fn into(self) -> Message<K> {
@@ -27,15 +25,125 @@ impl<K: Kind> Into<Message<K>> for PaneMessage {
 }
 
 /// Root message type for our app.
+/// Fields produce messages which mutate the value they edit, `V`, and their working values, `W`.
+/// These are lifted until they mutate the whole object and all of its working values.
 #[derive(Debug, Clone)]
-pub enum Message<K, W = <<K as Kind>::Field as Field>::WorkingValues>
+pub enum Message<K, W = <<K as Kind>::Field as Field<K>>::WorkingValues, V = K>
 where
     W: 'static + std::fmt::Debug + Clone + Send,
+    V: 'static,
     K: Kind,
 {
     PaneMessage(PaneMessage),
     Select(K::Key),
     NewObject,
-    Mutate(Box<dyn Mutator<K>>, Box<dyn Mutator<W>>),
+    /// Remove the given object
+    Delete(K::Key),
+    /// Add a copy of the given object after it, and select the copy
+    Duplicate(K::Key),
+    /// Move the given object to a new position in the store
+    MoveTo(K::Key, usize),
+    Mutate(Box<dyn Mutator<V>>, Box<dyn Mutator<W>>),
+    /// Undo the last mutation
+    Undo,
+    /// Redo the last undone mutation
+    Redo,
+    /// Group every mutation until the matching [`Message::CommitTransaction`] into one undo step.
+    BeginTransaction,
+    /// Finish the current transaction. This also stops the last mutation's session from merging with later ones.
+    CommitTransaction,
+    /// Open a document. If no path is given, the user is asked for one.
+    Open(Option<PathBuf>),
+    /// Save the document to wherever it was last opened from or saved to.
+    Save,
+    /// Save the document somewhere new. If no path is given, the user is asked for one.
+    SaveAs(Option<PathBuf>),
+    /// Run a command, such as loading something in the background.
+    Task(Task<K>),
     Nop,
 }
+
+impl<K, W, V> Message<K, W, V>
+where
+    W: 'static + std::fmt::Debug + Clone + Send,
+    V: 'static,
+    K: Kind,
+{
+    /// Convert the working values mutation to a different type, leaving every other message as is.
+    /// This is used to lift messages from a field into the field that contains it.
+    pub fn map_working<X, F>(self, f: F) -> Message<K, X, V>
+    where
+        X: 'static + std::fmt::Debug + Clone + Send,
+        F: FnOnce(Box<dyn Mutator<W>>) -> Box<dyn Mutator<X>>,
+    {
+        self.map(|v| v, f)
+    }
+
+    /// Convert the value mutation to a different type, leaving every other message as is.
+    /// This is used to lift messages from a field over part of an object into one over the whole thing.
+    pub fn map_value<U, F>(self, f: F) -> Message<K, W, U>
+    where
+        U: 'static,
+        F: FnOnce(Box<dyn Mutator<V>>) -> Box<dyn Mutator<U>>,
+    {
+        self.map(f, |w| w)
+    }
+
+    /// Convert both mutations to different types, leaving every other message as is.
+    pub fn map<U, X, FV, FW>(self, fv: FV, fw: FW) -> Message<K, X, U>
+    where
+        U: 'static,
+        X: 'static + std::fmt::Debug + Clone + Send,
+        FV: FnOnce(Box<dyn Mutator<V>>) -> Box<dyn Mutator<U>>,
+        FW: FnOnce(Box<dyn Mutator<W>>) -> Box<dyn Mutator<X>>,
+    {
+        match self {
+            Message::Mutate(v, w) => Message::Mutate(fv(v), fw(w)),
+            Message::PaneMessage(m) => Message::PaneMessage(m),
+            Message::Select(s) => Message::Select(s),
+            Message::NewObject => Message::NewObject,
+            Message::Delete(k) => Message::Delete(k),
+            Message::Duplicate(k) => Message::Duplicate(k),
+            Message::MoveTo(k, i) => Message::MoveTo(k, i),
+            Message::Undo => Message::Undo,
+            Message::Redo => Message::Redo,
+            Message::BeginTransaction => Message::BeginTransaction,
+            Message::CommitTransaction => Message::CommitTransaction,
+            Message::Open(p) => Message::Open(p),
+            Message::Save => Message::Save,
+            Message::SaveAs(p) => Message::SaveAs(p),
+            Message::Task(t) => Message::Task(t),
+            Message::Nop => Message::Nop,
+        }
+    }
+}
+
+/// Makes a command for the app to run. Messages have to be cloneable, so this holds a function making the command rather than the command itself.
+pub struct Task<K: Kind>(Arc<dyn Fn() -> Command<Message<K>> + Send + Sync>);
+
+impl<K: Kind> Task<K> {
+    /// Create a task which runs the command made by the given function
+    pub fn new<F>(f: F) -> Self
+    where
+        F: 'static + Fn() -> Command<Message<K>> + Send + Sync,
+    {
+        Self(Arc::new(f))
+    }
+
+    /// Get the command to run
+    pub fn command(&self) -> Command<Message<K>> {
+        (self.0)()
+    }
+}
+
+impl<K: Kind> Clone for Task<K> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<K: Kind> Debug for Task<K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Task")
+    }
+}
@@ -1,13 +1,23 @@
 //! Code related to mutation of generic [`crate::Kind`]s.
 
-use crate::lens::Lens;
+use crate::lens::{Lens, Prism};
 use dyn_clone::{clone_trait_object, DynClone};
-use std::{fmt::Debug, marker::PhantomData};
+use std::{
+    fmt::Debug,
+    marker::PhantomData,
+    sync::atomic::{AtomicU64, Ordering},
+};
 
 /// Describes a mutation that should be applied to an object
 pub trait Mutator<T>: Debug + Send + DynClone {
-    /// Apply the mutation to the given target.
-    fn apply(self: Box<Self>, target: &mut T);
+    /// Apply the mutation to the given target, returning a mutator that undoes it.
+    fn apply(self: Box<Self>, target: &mut T) -> Box<dyn Mutator<T>>;
+
+    /// The editing session this mutation is part of, if any.
+    /// Consecutive mutations to the same object in the same session are undone as one step.
+    fn session(&self) -> Option<Session> {
+        None
+    }
 }
 
 clone_trait_object!(<T> Mutator<T>);
@@ -16,22 +26,87 @@ clone_trait_object!(<T> Mutator<T>);
 #[derive(Debug, Clone)]
 pub struct NopMutator;
 impl<T> Mutator<T> for NopMutator {
-    fn apply(self: Box<Self>, _target: &mut T) {
-        ()
+    fn apply(self: Box<Self>, _target: &mut T) -> Box<dyn Mutator<T>> {
+        self
+    }
+}
+
+/// Identifies a run of edits which should be undone together, such as the keystrokes typed into a
+/// text input while it has focus. Each session is unique, so it should only be used by one field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Session(u64);
+impl Session {
+    /// Start a new session
+    pub fn new() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+
+        Self(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+impl Default for Session {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 /// A mutator which uses a lens to set the new value
 #[derive(Debug, Clone)]
-pub struct LensSet<L: Lens>(L::Target);
+pub struct LensSet<L: Lens>(L::Target, Option<Session>);
 impl<L: Lens> LensSet<L> {
     pub fn new(new: L::Target) -> Self {
-        Self(new)
+        Self(new, None)
+    }
+
+    /// Set the value as part of the given session, so that it's merged with the writes around it.
+    pub fn in_session(new: L::Target, session: Session) -> Self {
+        Self(new, Some(session))
     }
 }
-impl<S, T: Debug + Clone + Send, L: Lens<Source = S, Target = T>> Mutator<S> for LensSet<L> {
-    fn apply(self: Box<Self>, target: &mut S) {
-        *L::get_mut(target) = self.0.clone()
+impl<S, T, L> Mutator<S> for LensSet<L>
+where
+    T: 'static + Debug + Clone + Send,
+    L: 'static + Lens<Source = S, Target = T>,
+{
+    /// The inverse sets the value back to what it was before the write.
+    fn apply(self: Box<Self>, target: &mut S) -> Box<dyn Mutator<S>> {
+        let old = std::mem::replace(L::get_mut(target), self.0);
+
+        Box::new(LensSet::<L>::new(old))
+    }
+
+    fn session(&self) -> Option<Session> {
+        self.1
+    }
+}
+
+/// A mutator which uses a prism to set the new value. If the prism doesn't match, nothing happens.
+#[derive(Debug, Clone)]
+pub struct PrismSet<P: Prism>(P::Target, Option<Session>);
+impl<P: Prism> PrismSet<P> {
+    pub fn new(new: P::Target) -> Self {
+        Self(new, None)
+    }
+
+    /// Set the value as part of the given session, so that it's merged with the writes around it.
+    pub fn in_session(new: P::Target, session: Session) -> Self {
+        Self(new, Some(session))
+    }
+}
+impl<S, T, P> Mutator<S> for PrismSet<P>
+where
+    T: 'static + Debug + Clone + Send,
+    P: 'static + Prism<Source = S, Target = T>,
+{
+    /// The inverse sets the value back to what it was before the write.
+    fn apply(self: Box<Self>, target: &mut S) -> Box<dyn Mutator<S>> {
+        match P::get_mut(target) {
+            Some(x) => Box::new(PrismSet::<P>::new(std::mem::replace(x, self.0))),
+            None => Box::new(NopMutator),
+        }
+    }
+
+    fn session(&self) -> Option<Session> {
+        self.1
     }
 }
 
@@ -43,11 +118,183 @@ impl<L: Lens> InnerMutation<L> {
         Self(m, PhantomData)
     }
 }
-impl<L: Lens> Mutator<L::Source> for InnerMutation<L>
+impl<L: 'static + Lens> Mutator<L::Source> for InnerMutation<L>
 where
     L::Target: Debug + Clone + Send,
 {
-    fn apply(self: Box<Self>, target: &mut L::Source) {
-        self.0.apply(L::get_mut(target))
+    fn apply(self: Box<Self>, target: &mut L::Source) -> Box<dyn Mutator<L::Source>> {
+        Box::new(InnerMutation::<L>::new(self.0.apply(L::get_mut(target))))
+    }
+
+    fn session(&self) -> Option<Session> {
+        self.0.session()
+    }
+}
+
+/// Mutates an object by first applying a prism, then another mutator. If the prism doesn't match, nothing happens.
+#[derive(Debug, Clone)]
+pub struct PrismMutation<P: Prism>(Box<dyn Mutator<P::Target>>, PhantomData<P>);
+impl<P: Prism> PrismMutation<P> {
+    pub fn new(m: Box<dyn Mutator<P::Target>>) -> Self {
+        Self(m, PhantomData)
+    }
+}
+impl<P: 'static + Prism> Mutator<P::Source> for PrismMutation<P>
+where
+    P::Target: Debug + Clone + Send,
+{
+    fn apply(self: Box<Self>, target: &mut P::Source) -> Box<dyn Mutator<P::Source>> {
+        match P::get_mut(target) {
+            Some(x) => Box::new(PrismMutation::<P>::new(self.0.apply(x))),
+            None => Box::new(NopMutator),
+        }
+    }
+
+    fn session(&self) -> Option<Session> {
+        self.0.session()
+    }
+}
+
+/// Mutates one element of a Vec, like an [`InnerMutation`] with a lens to that index.
+/// If there's no element at the index, nothing happens.
+#[derive(Debug, Clone)]
+pub struct IndexMutation<T>(usize, Box<dyn Mutator<T>>);
+impl<T> IndexMutation<T> {
+    pub fn new(index: usize, m: Box<dyn Mutator<T>>) -> Self {
+        Self(index, m)
+    }
+}
+impl<T: 'static + Debug + Clone + Send> Mutator<Vec<T>> for IndexMutation<T> {
+    fn apply(self: Box<Self>, target: &mut Vec<T>) -> Box<dyn Mutator<Vec<T>>> {
+        match target.get_mut(self.0) {
+            Some(x) => Box::new(IndexMutation::new(self.0, self.1.apply(x))),
+            None => Box::new(NopMutator),
+        }
+    }
+
+    fn session(&self) -> Option<Session> {
+        self.1.session()
+    }
+}
+
+/// Inserts an element into a Vec. Indices past the end add it to the end.
+#[derive(Debug, Clone)]
+pub struct VecInsert<T>(usize, T);
+impl<T> VecInsert<T> {
+    pub fn new(index: usize, value: T) -> Self {
+        Self(index, value)
+    }
+}
+impl<T: 'static + Debug + Clone + Send> Mutator<Vec<T>> for VecInsert<T> {
+    fn apply(self: Box<Self>, target: &mut Vec<T>) -> Box<dyn Mutator<Vec<T>>> {
+        let index = self.0.min(target.len());
+        target.insert(index, self.1);
+
+        Box::new(VecRemove::<T>::new(index))
+    }
+}
+
+/// Removes an element from a Vec. If there's no element at the index, nothing happens.
+#[derive(Debug, Clone)]
+pub struct VecRemove<T>(usize, PhantomData<T>);
+impl<T> VecRemove<T> {
+    pub fn new(index: usize) -> Self {
+        Self(index, PhantomData)
+    }
+}
+impl<T: 'static + Debug + Clone + Send> Mutator<Vec<T>> for VecRemove<T> {
+    fn apply(self: Box<Self>, target: &mut Vec<T>) -> Box<dyn Mutator<Vec<T>>> {
+        if self.0 < target.len() {
+            Box::new(VecInsert::new(self.0, target.remove(self.0)))
+        } else {
+            Box::new(NopMutator)
+        }
+    }
+}
+
+/// Swaps two elements of a Vec. If either index is out of bounds, nothing happens.
+#[derive(Debug, Clone)]
+pub struct VecSwap(usize, usize);
+impl VecSwap {
+    pub fn new(a: usize, b: usize) -> Self {
+        Self(a, b)
+    }
+}
+impl<T> Mutator<Vec<T>> for VecSwap {
+    /// Swapping is its own inverse.
+    fn apply(self: Box<Self>, target: &mut Vec<T>) -> Box<dyn Mutator<Vec<T>>> {
+        if self.0 < target.len() && self.1 < target.len() {
+            target.swap(self.0, self.1);
+
+            self
+        } else {
+            Box::new(NopMutator)
+        }
+    }
+}
+
+/// Mutates the value inside an Option. If it's `None`, nothing happens.
+#[derive(Debug, Clone)]
+pub struct SomeMutation<T>(Box<dyn Mutator<T>>);
+impl<T> SomeMutation<T> {
+    pub fn new(m: Box<dyn Mutator<T>>) -> Self {
+        Self(m)
+    }
+}
+impl<T: 'static + Debug + Clone + Send> Mutator<Option<T>> for SomeMutation<T> {
+    fn apply(self: Box<Self>, target: &mut Option<T>) -> Box<dyn Mutator<Option<T>>> {
+        match target {
+            Some(x) => Box::new(SomeMutation::new(self.0.apply(x))),
+            None => Box::new(NopMutator),
+        }
+    }
+
+    fn session(&self) -> Option<Session> {
+        self.0.session()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        lens::TupleHeadLens,
+        testing::{Thing, ThingNameLens},
+    };
+
+    #[test]
+    fn lens_set_inverse() {
+        let mut thing = Thing::new("a", 0);
+
+        let inverse = Box::new(LensSet::<ThingNameLens>::new("b".to_string())).apply(&mut thing);
+        assert_eq!("b", thing.name);
+
+        let redo = inverse.apply(&mut thing);
+        assert_eq!("a", thing.name);
+
+        redo.apply(&mut thing);
+        assert_eq!("b", thing.name);
+    }
+
+    #[test]
+    fn inner_mutation_inverse() {
+        let mut pair = (Thing::new("a", 0), 1);
+
+        let inverse = Box::new(InnerMutation::<TupleHeadLens<Thing, i32>>::new(Box::new(
+            LensSet::<ThingNameLens>::new("b".to_string()),
+        )))
+        .apply(&mut pair);
+        assert_eq!("b", pair.0.name);
+
+        inverse.apply(&mut pair);
+        assert_eq!((Thing::new("a", 0), 1), pair);
+    }
+
+    #[test]
+    fn nop_inverse() {
+        let mut thing = Thing::new("a", 0);
+
+        Box::new(NopMutator).apply(&mut thing).apply(&mut thing);
+        assert_eq!(Thing::new("a", 0), thing);
     }
 }
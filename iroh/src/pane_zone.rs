@@ -9,15 +9,92 @@ use crate::{
 use iced::{
     button,
     pane_grid::{self, Pane, TitleBar},
-    Button, Element, PaneGrid, Row, Text,
+    Button, Element, PaneGrid, Row, Subscription, Text,
 };
 
 /// Something which can be displayed in a pane
 pub trait Paneable<K: Kind, C: ObjectStore<K>> {
     fn view(&mut self, pane: Pane, app_state: &AppState<K, C>) -> Element<Message<K>>;
     fn title(&self) -> String;
+
+    /// Events this pane wants to hear about while it's open, such as timers or file changes.
+    fn subscription(&self, _app_state: &AppState<K, C>) -> Subscription<Message<K>> {
+        Subscription::none()
+    }
+}
+
+/// Makes a new pane
+type Constructor<K, C> = Box<dyn Fn() -> Box<dyn Paneable<K, C>>>;
+
+/// The panes a user can pick from to fill an empty pane, by name.
+/// By default, this has the outline and inspector panes. Register your own [`Paneable`]s to make them available too.
+pub struct PaneRegistry<K: Kind, C: ObjectStore<K>> {
+    /// In the order they were registered
+    panes: Vec<(&'static str, Constructor<K, C>)>,
+}
+
+impl<K: Kind, C: ObjectStore<K>> PaneRegistry<K, C> {
+    /// Create a registry with no panes in it
+    pub fn empty() -> Self {
+        Self { panes: vec![] }
+    }
+
+    /// Create a registry with the built in panes
+    pub fn new() -> Self {
+        Self::empty()
+            .with("Outline", OutlinePane::default)
+            .with("Inspector", InspectorPane::<K::Field>::default)
+    }
+
+    /// Make a pane available under the given name, replacing any pane already registered with that name.
+    pub fn register<P, F>(&mut self, name: &'static str, constructor: F) -> &mut Self
+    where
+        P: 'static + Paneable<K, C>,
+        F: 'static + Fn() -> P,
+    {
+        let constructor: Constructor<K, C> = Box::new(move || Box::new(constructor()));
+        match self.panes.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, c)) => *c = constructor,
+            None => self.panes.push((name, constructor)),
+        }
+
+        self
+    }
+
+    /// Like [`Self::register`], but for chaining when building a registry.
+    pub fn with<P, F>(mut self, name: &'static str, constructor: F) -> Self
+    where
+        P: 'static + Paneable<K, C>,
+        F: 'static + Fn() -> P,
+    {
+        self.register(name, constructor);
+        self
+    }
+
+    /// Get the names of every registered pane, in the order they were registered
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.panes.iter().map(|(n, _)| *n)
+    }
+
+    /// Make a new pane of the type registered with the given name
+    pub fn construct(&self, name: &str) -> Option<Box<dyn Paneable<K, C>>> {
+        self.panes
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, c)| c())
+    }
+}
+
+impl<K: Kind, C: ObjectStore<K>> Default for PaneRegistry<K, C> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
+/// How panes are arranged, with each pane given by the name it was registered with.
+/// Names that aren't in the registry become [`EmptyPane`]s.
+pub type PaneLayout = pane_grid::Configuration<&'static str>;
+
 /// A layout with a bunch of varying panes, with all the code to split, rearrange, and resize them.
 pub struct PaneZone<K: Kind, C: ObjectStore<K>> {
     panes: pane_grid::State<PaneState<K, C>>,
@@ -30,6 +107,36 @@ impl<K: Kind, C: ObjectStore<K>> PaneZone<K, C> {
         Self { panes }
     }
 
+    /// Create a new pane zone with the given layout, making its panes from the given registry
+    pub fn with_layout(layout: PaneLayout, registry: &PaneRegistry<K, C>) -> Self {
+        fn build<K: Kind, C: ObjectStore<K>>(
+            layout: PaneLayout,
+            registry: &PaneRegistry<K, C>,
+        ) -> pane_grid::Configuration<PaneState<K, C>> {
+            match layout {
+                pane_grid::Configuration::Split { axis, ratio, a, b } => {
+                    pane_grid::Configuration::Split {
+                        axis,
+                        ratio,
+                        a: Box::new(build(*a, registry)),
+                        b: Box::new(build(*b, registry)),
+                    }
+                }
+                pane_grid::Configuration::Pane(name) => {
+                    pane_grid::Configuration::Pane(PaneState::new(
+                        registry
+                            .construct(name)
+                            .unwrap_or_else(|| Box::new(EmptyPane::default())),
+                    ))
+                }
+            }
+        }
+
+        Self {
+            panes: pane_grid::State::with_configuration(build(layout, registry)),
+        }
+    }
+
     /// Get what to currently render
     pub fn view<'b>(&'b mut self, app_state: &AppState<K, C>) -> Element<'b, Message<K>> {
         PaneGrid::new(&mut self.panes, |pane, content| {
@@ -41,8 +148,18 @@ impl<K: Kind, C: ObjectStore<K>> PaneZone<K, C> {
         .into()
     }
 
-    /// Process the given message
-    pub fn update(&mut self, msg: PaneMessage) {
+    /// Get the events every open pane wants to hear about
+    pub fn subscription(&self, app_state: &AppState<K, C>) -> Subscription<Message<K>> {
+        Subscription::batch(
+            self.panes
+                .iter()
+                .map(|(_, p)| p.elem.subscription(app_state))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Process the given message, using the given registry to make any new panes
+    pub fn update(&mut self, msg: PaneMessage, registry: &PaneRegistry<K, C>) {
         match msg {
             PaneMessage::Split(axis, pane) => {
                 self.panes
@@ -64,14 +181,9 @@ impl<K: Kind, C: ObjectStore<K>> PaneZone<K, C> {
                     todo!()
                 }
             }
-            PaneMessage::Set(p, new) => {
-                if let Some(dst) = self.panes.get_mut(&p) {
-                    *dst = match new {
-                        NewPane::Outline => PaneState::new(Box::new(OutlinePane::default())),
-                        NewPane::Inspector => {
-                            PaneState::new(Box::new(InspectorPane::<K::Field>::default()))
-                        }
-                    };
+            PaneMessage::Set(p, NewPane(name)) => {
+                if let (Some(dst), Some(new)) = (self.panes.get_mut(&p), registry.construct(name)) {
+                    *dst = PaneState::new(new);
                 }
             }
         }
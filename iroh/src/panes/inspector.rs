@@ -1,11 +1,11 @@
 //! The inspector pane
 
 use crate::{app::AppState, message::Message, pane_zone::Paneable, Field, Kind, ObjectStore};
-use iced::{pane_grid::Pane, Column, Element, Text};
+use iced::{pane_grid::Pane, Column, Element, Subscription, Text};
 
 /// Shows the fields of the currently selected object.
-pub struct InspectorPane<F: Field>(F);
-impl<F: Field> Default for InspectorPane<F> {
+pub struct InspectorPane<F>(F);
+impl<F: Default> Default for InspectorPane<F> {
     fn default() -> Self {
         Self(Default::default())
     }
@@ -29,4 +29,8 @@ impl<K: Kind, C: ObjectStore<K>> Paneable<K, C> for InspectorPane<K::Field> {
     fn title(&self) -> String {
         "Inspector".to_string()
     }
+
+    fn subscription(&self, _app_state: &AppState<K, C>) -> Subscription<Message<K>> {
+        self.0.subscription()
+    }
 }
@@ -1,7 +1,7 @@
 pub mod inspector;
 pub mod outline;
 
-pub use crate::pane_zone::Paneable;
+pub use crate::pane_zone::{PaneLayout, PaneRegistry, Paneable};
 pub use outline::OutlinePane;
 
 use crate::{
@@ -11,18 +11,14 @@ use crate::{
 };
 use iced::{button, pane_grid::Pane, Button, Column, Element, Text};
 
-/// An empty pane, which provides buttons to swap it out for any other pane.
+/// An empty pane, which provides buttons to swap it out for any registered pane.
 pub struct EmptyPane {
-    outline_state: button::State,
-    inspector_state: button::State,
+    states: Vec<button::State>,
 }
 impl EmptyPane {
     /// Create a new empty pane
     pub fn new() -> Self {
-        Self {
-            outline_state: button::State::default(),
-            inspector_state: button::State::default(),
-        }
+        Self { states: vec![] }
     }
 }
 impl Default for EmptyPane {
@@ -32,21 +28,21 @@ impl Default for EmptyPane {
 }
 impl<K: Kind, C: ObjectStore<K>> Paneable<K, C> for EmptyPane {
     fn view(&mut self, pane: Pane, app_state: &AppState<K, C>) -> Element<Message<K>> {
-        Column::with_children(vec![
-            // Outline
-            Button::new(&mut self.outline_state, Text::new("Outline"))
-                .style(app_state.theme().button_primary())
-                .on_press(PaneMessage::Set(pane, NewPane::Outline).into())
-                .into(),
-            // Inspector
-            Button::new(&mut self.inspector_state, Text::new("Inspector"))
-                .style(app_state.theme().button_primary())
-                .on_press(PaneMessage::Set(pane, NewPane::Inspector).into())
-                .into(),
-        ])
-        .padding(10)
-        .spacing(10)
-        .into()
+        let registry = app_state.panes();
+        while self.states.len() < registry.names().count() {
+            self.states.push(button::State::default());
+        }
+
+        let mut col = Column::new().padding(10).spacing(10);
+        for (name, s) in registry.names().zip(self.states.iter_mut()) {
+            col = col.push(
+                Button::new(s, Text::new(name))
+                    .style(app_state.theme().button_primary())
+                    .on_press(PaneMessage::Set(pane, NewPane(name)).into()),
+            );
+        }
+
+        col.into()
     }
 
     fn title(&self) -> String {
@@ -10,23 +10,66 @@ use iced::{
 pub struct OutlinePane {
     scrollable_state: scrollable::State,
     new_state: button::State,
+    duplicate_state: button::State,
+    delete_state: button::State,
+    up_state: button::State,
+    down_state: button::State,
     states: Vec<button::State>,
 }
 
 impl<K: Kind, C: ObjectStore<K>> Paneable<K, C> for OutlinePane {
     fn view(&mut self, _pane: Pane, app_state: &AppState<K, C>) -> Element<Message<K>> {
-        let controls = Row::with_children(vec![Button::new(&mut self.new_state, Text::new("+"))
+        let mut controls = vec![Button::new(&mut self.new_state, Text::new("+"))
             .on_press(Message::NewObject)
             .style(app_state.theme().button_primary())
-            .into()])
-        .align_items(Align::End);
+            .into()];
+
+        // Actions on the selected object
+        if let Some(k) = app_state.selected_key() {
+            let index = app_state
+                .container()
+                .keys()
+                .position(|x| x == k)
+                .unwrap_or(0);
+
+            controls.push(
+                Button::new(&mut self.duplicate_state, Text::new("Duplicate"))
+                    .on_press(Message::Duplicate(k.clone()))
+                    .style(app_state.theme().button_subtle())
+                    .into(),
+            );
+            controls.push(
+                Button::new(&mut self.delete_state, Text::new("Delete"))
+                    .on_press(Message::Delete(k.clone()))
+                    .style(app_state.theme().button_subtle())
+                    .into(),
+            );
+
+            let mut up = Button::new(&mut self.up_state, Text::new("Up"))
+                .style(app_state.theme().button_subtle());
+            if index > 0 {
+                up = up.on_press(Message::MoveTo(k.clone(), index - 1));
+            }
+            controls.push(up.into());
+
+            let mut down = Button::new(&mut self.down_state, Text::new("Down"))
+                .style(app_state.theme().button_subtle());
+            if index + 1 < app_state.container().count() {
+                down = down.on_press(Message::MoveTo(k.clone(), index + 1));
+            }
+            controls.push(down.into());
+        }
+
+        let controls = Row::with_children(controls)
+            .spacing(4)
+            .align_items(Align::End);
         let mut list = Scrollable::new(&mut self.scrollable_state);
 
         while self.states.len() < app_state.container().count() {
             self.states.push(button::State::default());
         }
 
-        for ((k, _v, _), s) in app_state.container().items().zip(self.states.iter_mut()) {
+        for (k, s) in app_state.container().keys().zip(self.states.iter_mut()) {
             let selected = app_state.is_selected(k);
             list = list.push(
                 Button::new(s, Text::new(format!("{:?}", k)))
@@ -53,6 +96,10 @@ impl Default for OutlinePane {
         Self {
             scrollable_state: scrollable::State::default(),
             new_state: button::State::default(),
+            duplicate_state: button::State::default(),
+            delete_state: button::State::default(),
+            up_state: button::State::default(),
+            down_state: button::State::default(),
             states: vec![],
         }
     }
@@ -0,0 +1,321 @@
+use crate::{Field, Key, Kind, ObjectStore};
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+};
+
+/// An object and its working values, as stored in a map.
+type Slot<K> = (K, <<K as Kind>::Field as Field<K>>::WorkingValues);
+
+/// Container backed by a [`HashMap`], for O(1) lookups.
+pub type HashMapContainer<K> = MapContainer<K, HashMap<<K as Kind>::Key, Slot<K>>>;
+
+/// Container backed by a [`BTreeMap`], for O(log n) lookups when keys aren't hashable.
+pub type BTreeMapContainer<K> = MapContainer<K, BTreeMap<<K as Kind>::Key, Slot<K>>>;
+
+/// A map from keys to values, so that [`MapContainer`] can use different kinds of map.
+pub trait Map<Q, V>: Default {
+    fn get(&self, key: &Q) -> Option<&V>;
+    fn get_mut(&mut self, key: &Q) -> Option<&mut V>;
+    fn insert(&mut self, key: Q, value: V);
+    fn remove(&mut self, key: &Q) -> Option<V>;
+    fn contains_key(&self, key: &Q) -> bool;
+}
+
+impl<Q: Hash + Eq, V> Map<Q, V> for HashMap<Q, V> {
+    fn get(&self, key: &Q) -> Option<&V> {
+        HashMap::get(self, key)
+    }
+
+    fn get_mut(&mut self, key: &Q) -> Option<&mut V> {
+        HashMap::get_mut(self, key)
+    }
+
+    fn insert(&mut self, key: Q, value: V) {
+        HashMap::insert(self, key, value);
+    }
+
+    fn remove(&mut self, key: &Q) -> Option<V> {
+        HashMap::remove(self, key)
+    }
+
+    fn contains_key(&self, key: &Q) -> bool {
+        HashMap::contains_key(self, key)
+    }
+}
+
+impl<Q: Ord, V> Map<Q, V> for BTreeMap<Q, V> {
+    fn get(&self, key: &Q) -> Option<&V> {
+        BTreeMap::get(self, key)
+    }
+
+    fn get_mut(&mut self, key: &Q) -> Option<&mut V> {
+        BTreeMap::get_mut(self, key)
+    }
+
+    fn insert(&mut self, key: Q, value: V) {
+        BTreeMap::insert(self, key, value);
+    }
+
+    fn remove(&mut self, key: &Q) -> Option<V> {
+        BTreeMap::remove(self, key)
+    }
+
+    fn contains_key(&self, key: &Q) -> bool {
+        BTreeMap::contains_key(self, key)
+    }
+}
+
+/// Map backed container. Lookups go through the map, while a list of keys keeps iteration in a stable order.
+///
+/// Getting, changing and adding objects don't touch the list of keys, except to push onto it.
+/// Removing, duplicating and moving an object have to find it in the list and shift the keys after it,
+/// so they're O(n) like with a [`super::VecContainer`], though they only move keys rather than whole objects.
+pub struct MapContainer<K: Kind, M> {
+    map: M,
+    order: Vec<K::Key>,
+
+    /// The last key we allocated, to pass to [`Key::next`]
+    last: Option<K::Key>,
+}
+
+impl<K: Kind, M: Map<K::Key, Slot<K>>> MapContainer<K, M> {
+    /// Get the index of the object with the given key when iterating
+    fn position(&self, key: &K::Key) -> Option<usize> {
+        self.order.iter().position(|k| k == key)
+    }
+
+    /// Get a key that isn't in use yet.
+    /// Keys inserted by [`ObjectStore::insert_with_key`] might be in the way, so those are skipped.
+    fn next_key(&mut self) -> K::Key {
+        let mut next = match &self.last {
+            Some(last) => K::Key::next(last),
+            None => K::Key::first(),
+        };
+        while self.map.contains_key(&next) {
+            next = K::Key::next(&next);
+        }
+        self.last = Some(next.clone());
+
+        next
+    }
+}
+
+impl<K: Kind, M: Map<K::Key, Slot<K>>> ObjectStore<K> for MapContainer<K, M> {
+    fn empty() -> Self {
+        Self {
+            map: M::default(),
+            order: vec![],
+            last: None,
+        }
+    }
+
+    fn insert_with(&mut self, value: K) -> &K::Key {
+        let key = self.next_key();
+        self.map.insert(
+            key.clone(),
+            (value, <K::Field as Field<K>>::WorkingValues::default()),
+        );
+        self.order.push(key);
+
+        self.order.last().unwrap()
+    }
+
+    fn insert_with_key(&mut self, key: K::Key, value: K) -> bool {
+        if self.map.contains_key(&key) {
+            return false;
+        }
+
+        self.last = Some(key.clone());
+        self.map.insert(
+            key.clone(),
+            (value, <K::Field as Field<K>>::WorkingValues::default()),
+        );
+        self.order.push(key);
+
+        true
+    }
+
+    fn remove(&mut self, key: &K::Key) -> Option<Slot<K>> {
+        let slot = self.map.remove(key)?;
+        if let Some(i) = self.position(key) {
+            self.order.remove(i);
+        }
+
+        Some(slot)
+    }
+
+    fn duplicate(&mut self, key: &K::Key) -> Option<&K::Key> {
+        let value = self.map.get(key)?.0.clone();
+        let i = self.position(key)?;
+        let next = self.next_key();
+        self.map.insert(
+            next.clone(),
+            (value, <K::Field as Field<K>>::WorkingValues::default()),
+        );
+        self.order.insert(i + 1, next);
+
+        Some(&self.order[i + 1])
+    }
+
+    fn move_to(&mut self, key: &K::Key, index: usize) -> bool {
+        match self.position(key) {
+            Some(i) => {
+                let key = self.order.remove(i);
+                let index = index.min(self.order.len());
+                self.order.insert(index, key);
+
+                true
+            }
+            None => false,
+        }
+    }
+
+    type Items<'a> = MapItems<'a, K, M>;
+    fn items<'a>(&'a self) -> Self::Items<'a> {
+        MapItems {
+            keys: self.order.iter(),
+            map: &self.map,
+        }
+    }
+
+    type Keys<'a> = std::slice::Iter<'a, K::Key>;
+    fn keys<'a>(&'a self) -> Self::Keys<'a> {
+        self.order.iter()
+    }
+
+    type Values<'a> = MapValues<'a, K, M>;
+    fn values<'a>(&'a self) -> Self::Values<'a> {
+        MapValues(self.items())
+    }
+
+    fn get(&self, key: &K::Key) -> Option<(&K, &<K::Field as Field<K>>::WorkingValues)> {
+        self.map.get(key).map(|(v, w)| (v, w))
+    }
+
+    fn get_mut(
+        &mut self,
+        key: &K::Key,
+    ) -> Option<(&mut K, &mut <K::Field as Field<K>>::WorkingValues)> {
+        self.map.get_mut(key).map(|(v, w)| (v, w))
+    }
+
+    fn exists(&self, key: &K::Key) -> bool {
+        self.map.contains_key(key)
+    }
+
+    fn count(&self) -> usize {
+        self.order.len()
+    }
+}
+
+/// Iterates over the objects in a [`MapContainer`], in order.
+pub struct MapItems<'a, K: Kind, M> {
+    keys: std::slice::Iter<'a, K::Key>,
+    map: &'a M,
+}
+
+impl<'a, K: Kind, M: Map<K::Key, Slot<K>>> Iterator for MapItems<'a, K, M> {
+    type Item = (&'a K::Key, &'a K, &'a <K::Field as Field<K>>::WorkingValues);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let k = self.keys.next()?;
+        self.map.get(k).map(|(v, w)| (k, v, w))
+    }
+}
+
+/// Iterates over the values in a [`MapContainer`], in order.
+pub struct MapValues<'a, K: Kind, M>(MapItems<'a, K, M>);
+
+impl<'a, K: Kind, M: Map<K::Key, Slot<K>>> Iterator for MapValues<'a, K, M> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(_k, v, _w)| v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Thing;
+
+    /// A store with objects named "a", "b" and "c", with keys 0, 1 and 2
+    fn abc<M: Map<u32, Slot<Thing>>>() -> MapContainer<Thing, M> {
+        let mut store = MapContainer::empty();
+        for name in ["a", "b", "c"].iter() {
+            store.insert_with(Thing::new(name, 0));
+        }
+
+        store
+    }
+
+    fn names<M: Map<u32, Slot<Thing>>>(store: &MapContainer<Thing, M>) -> Vec<&str> {
+        store.values().map(|t| t.name.as_str()).collect()
+    }
+
+    fn keys<M: Map<u32, Slot<Thing>>>(store: &MapContainer<Thing, M>) -> Vec<u32> {
+        store.keys().copied().collect()
+    }
+
+    /// Run the same checks against both kinds of map
+    fn order_is_stable<M: Map<u32, Slot<Thing>>>() {
+        let mut store = abc::<M>();
+        assert_eq!(vec!["a", "b", "c"], names(&store));
+
+        assert_eq!(Some(&3), store.duplicate(&0));
+        assert_eq!(vec!["a", "a", "b", "c"], names(&store));
+        assert_eq!(vec![0, 3, 1, 2], keys(&store));
+
+        assert_eq!("b", store.remove(&1).unwrap().0.name);
+        assert!(!store.exists(&1));
+        assert_eq!(vec![0, 3, 2], keys(&store));
+
+        assert!(store.move_to(&2, 0));
+        assert_eq!(vec![2, 0, 3], keys(&store));
+        assert!(store.move_to(&2, 100));
+        assert_eq!(vec![0, 3, 2], keys(&store));
+        assert!(!store.move_to(&1, 0));
+
+        assert_eq!(4, *store.insert_with(Thing::new("d", 0)));
+        assert_eq!(vec!["a", "a", "c", "d"], names(&store));
+        assert_eq!(4, store.count());
+        assert_eq!(
+            keys(&store),
+            store.items().map(|(k, _, _)| *k).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn hash_map_order() {
+        order_is_stable::<HashMap<u32, Slot<Thing>>>();
+    }
+
+    #[test]
+    fn btree_map_order() {
+        order_is_stable::<BTreeMap<u32, Slot<Thing>>>();
+    }
+
+    #[test]
+    fn insert_with_key() {
+        let mut store = MapContainer::<Thing, HashMap<_, _>>::empty();
+        assert!(store.insert_with_key(1, Thing::new("b", 0)));
+        assert!(store.insert_with_key(0, Thing::new("a", 0)));
+        assert!(!store.insert_with_key(1, Thing::new("c", 0)));
+        assert_eq!(vec!["b", "a"], names(&store));
+        assert_eq!(&2, store.insert_with(Thing::new("c", 0)));
+    }
+
+    #[test]
+    fn get_mut() {
+        let mut store = abc::<HashMap<_, _>>();
+        let (thing, working) = store.get_mut(&1).unwrap();
+        thing.size = 5;
+        *working = Some("5.".to_string());
+
+        let (thing, working) = store.get(&1).unwrap();
+        assert_eq!(5, thing.size);
+        assert_eq!(&Some("5.".to_string()), working);
+        assert!(store.get(&9).is_none());
+    }
+}
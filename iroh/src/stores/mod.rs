@@ -1,20 +1,56 @@
 //! Stores are used to keep track of what's in a file while the editor is open.
+//! A SQLite backed store, for documents too big to load all at once, is available behind the `sqlite` feature.
+mod map;
+#[cfg(feature = "sqlite")]
+mod sqlite;
 mod vec;
 
 use crate::{Field, Kind};
+use std::{error::Error, path::Path};
 
+pub use map::{BTreeMapContainer, HashMapContainer, Map, MapContainer};
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteContainer;
 pub use vec::VecContainer;
 
+/// Something that went wrong while a store was reading or writing its own file.
+pub type StoreError = Box<dyn Error>;
+
 /// A container for objects of differing kinds. Usually, this will be your filetype.
 pub trait ObjectStore<K: 'static + Kind> {
     fn empty() -> Self;
-    fn add(&mut self) -> &K::Key;
+
+    /// Add a new object with the default value, returning its key.
+    fn add(&mut self) -> &K::Key {
+        self.insert_with(K::default())
+    }
+
+    /// Add a new object with the given value at the end, returning its key.
+    fn insert_with(&mut self, value: K) -> &K::Key;
+
+    /// Add an object with the given key and value at the end, such as when reading a document that saved its keys.
+    /// Keys allocated afterwards won't collide with it. Returns false if the key is already in use.
+    fn insert_with_key(&mut self, key: K::Key, value: K) -> bool;
+
+    /// Remove an object, returning it along with its working values.
+    fn remove(
+        &mut self,
+        key: &K::Key,
+    ) -> Option<(K, <<K as Kind>::Field as Field<K>>::WorkingValues)>;
+
+    /// Add a copy of an object straight after it, returning the new key.
+    /// Working values aren't copied.
+    fn duplicate(&mut self, key: &K::Key) -> Option<&K::Key>;
+
+    /// Move an object so it's at the given position when iterating. Indices past the end move it to the end.
+    /// Returns false if there's no such object.
+    fn move_to(&mut self, key: &K::Key, index: usize) -> bool;
 
     type Items<'a>: Iterator<
         Item = (
             &'a K::Key,
             &'a K,
-            &'a <<K as Kind>::Field as Field>::WorkingValues,
+            &'a <<K as Kind>::Field as Field<K>>::WorkingValues,
         ),
     >;
     fn items<'a>(&'a self) -> Self::Items<'a>;
@@ -25,12 +61,27 @@ pub trait ObjectStore<K: 'static + Kind> {
     type Values<'a>: Iterator<Item = &'a K>;
     fn values<'a>(&'a self) -> Self::Values<'a>;
 
-    fn get(&self, key: &K::Key) -> Option<(&K, &<<K as Kind>::Field as Field>::WorkingValues)>;
+    fn get(&self, key: &K::Key) -> Option<(&K, &<<K as Kind>::Field as Field<K>>::WorkingValues)>;
     fn get_mut(
         &mut self,
         key: &K::Key,
-    ) -> Option<(&mut K, &mut <<K as Kind>::Field as Field>::WorkingValues)>;
+    ) -> Option<(&mut K, &mut <<K as Kind>::Field as Field<K>>::WorkingValues)>;
 
     fn exists(&self, key: &K::Key) -> bool;
     fn count(&self) -> usize;
+
+    /// Open the document at `path`, for stores which manage their own file such as a database.
+    /// Returns `None` if the store should be filled by the app's codec instead, which is the default.
+    fn open(_path: &Path) -> Option<Result<Self, StoreError>>
+    where
+        Self: Sized,
+    {
+        None
+    }
+
+    /// Write the store's contents to `path`, for stores which manage their own file.
+    /// Returns `None` if the app's codec should write it instead, which is the default.
+    fn save(&mut self, _path: &Path) -> Option<Result<(), StoreError>> {
+        None
+    }
 }
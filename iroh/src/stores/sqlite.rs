@@ -0,0 +1,707 @@
+use crate::{
+    stores::{ObjectStore, StoreError},
+    Field, Key, Kind,
+};
+use once_cell::unsync::OnceCell;
+use rusqlite::{params, Connection, OpenFlags, NO_PARAMS};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    cell::Cell,
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// An object and its working values, once they've been loaded from the database.
+type Loaded<K> = (K, <<K as Kind>::Field as Field<K>>::WorkingValues);
+
+/// Creates the table objects are kept in, if it's not there already.
+const CREATE_TABLE: &str = "CREATE TABLE IF NOT EXISTS objects (
+    key TEXT PRIMARY KEY,
+    position INTEGER NOT NULL,
+    value TEXT NOT NULL
+)";
+
+/// One object in the store, which might not have been read from the database yet.
+struct Slot<K: Kind> {
+    value: OnceCell<Loaded<K>>,
+
+    /// The object as it's stored in the database, once it's been read or written
+    saved: OnceCell<String>,
+
+    /// Whether the object might have changed since it was last written back.
+    /// It's compared with what was saved before being written, since this is set by any mutable access.
+    dirty: bool,
+
+    /// Whether reading the object failed
+    broken: Cell<bool>,
+}
+
+impl<K: Kind> Slot<K> {
+    /// An object that's in the database, but hasn't been read yet
+    fn unread() -> Self {
+        Self {
+            value: OnceCell::new(),
+            saved: OnceCell::new(),
+            dirty: false,
+            broken: Cell::new(false),
+        }
+    }
+}
+
+/// Container backed by a SQLite database, for documents too big to load all at once.
+///
+/// Only keys are read when a document is opened. Objects are read the first time they're looked at,
+/// and changed objects are written back when the document is saved. Working values are only kept in memory.
+///
+/// Keys and objects are stored as JSON, in a table called `objects`.
+/// Once an object has been found to be unreadable, it's treated as if it doesn't exist,
+/// except that removing it still deletes it from the database.
+pub struct SqliteContainer<K: Kind> {
+    /// The database the document was opened from or last saved to, or `None` for a new document.
+    /// New documents are kept entirely in memory until they're first saved.
+    conn: Option<Connection>,
+
+    /// Where the database is
+    path: Option<PathBuf>,
+
+    slots: BTreeMap<K::Key, Slot<K>>,
+    order: Vec<K::Key>,
+
+    /// Objects removed since the last save, which need deleting from the database
+    deleted: Vec<K::Key>,
+
+    /// Whether objects have been moved since the last save
+    order_dirty: bool,
+
+    /// How many objects couldn't be read
+    broken: Cell<usize>,
+
+    /// The last key we allocated, to pass to [`Key::next`]
+    last: Option<K::Key>,
+}
+
+impl<K> SqliteContainer<K>
+where
+    K: Kind + Serialize + DeserializeOwned,
+    K::Key: Ord + Serialize + DeserializeOwned,
+{
+    /// Open the database at `path`, reading only the keys of the objects in it.
+    fn open_file(path: &Path) -> Result<Self, StoreError> {
+        let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_WRITE)?;
+        conn.execute(CREATE_TABLE, NO_PARAMS)?;
+
+        let mut order = vec![];
+        {
+            let mut statement = conn.prepare("SELECT key FROM objects ORDER BY position")?;
+            let mut rows = statement.query(NO_PARAMS)?;
+            while let Some(row) = rows.next()? {
+                let key: String = row.get(0)?;
+                order.push(serde_json::from_str::<K::Key>(&key)?);
+            }
+        }
+
+        let slots = order.iter().map(|k| (k.clone(), Slot::unread())).collect();
+        let last = order.iter().max().cloned();
+
+        Ok(Self {
+            conn: Some(conn),
+            path: Some(canonical(path)?),
+            slots,
+            order,
+            deleted: vec![],
+            order_dirty: false,
+            broken: Cell::new(0),
+            last,
+        })
+    }
+
+    /// Whether the object with the given key is known to be unreadable
+    fn is_broken(&self, key: &K::Key) -> bool {
+        matches!(self.slots.get(key), Some(s) if s.broken.get())
+    }
+
+    /// Get the index of the object with the given key in `order`, which includes unreadable objects
+    fn position(&self, key: &K::Key) -> Option<usize> {
+        self.order.iter().position(|k| k == key)
+    }
+
+    /// Get the index in `order` of the object that's at `index` when iterating, which skips unreadable objects.
+    /// Indices past the end give the end.
+    fn order_index(&self, index: usize) -> usize {
+        self.order
+            .iter()
+            .enumerate()
+            .filter(|(_, k)| !self.is_broken(k))
+            .nth(index)
+            .map_or(self.order.len(), |(i, _)| i)
+    }
+
+    /// Get a key that isn't in use yet.
+    /// Keys inserted by [`ObjectStore::insert_with_key`] might be in the way, so those are skipped.
+    fn next_key(&mut self) -> K::Key {
+        let mut next = match &self.last {
+            Some(last) => K::Key::next(last),
+            None => K::Key::first(),
+        };
+        while self.slots.contains_key(&next) {
+            next = K::Key::next(&next);
+        }
+        self.last = Some(next.clone());
+
+        next
+    }
+
+    /// Add an object that's only in memory so far
+    fn insert_at(&mut self, index: usize, key: K::Key, value: K) {
+        let slot = Slot {
+            dirty: true,
+            ..Slot::unread()
+        };
+        let _ = slot
+            .value
+            .set((value, <K::Field as Field<K>>::WorkingValues::default()));
+
+        self.slots.insert(key.clone(), slot);
+        self.order.insert(index, key);
+    }
+
+    /// Get an object, reading it from the database if this is the first time it's needed.
+    /// If it can't be read, it's marked as broken.
+    fn load(&self, key: &K::Key) -> Option<&Loaded<K>> {
+        let slot = self.slots.get(key)?;
+        if slot.broken.get() {
+            return None;
+        }
+
+        let loaded = slot
+            .value
+            .get_or_try_init(|| -> Result<_, StoreError> {
+                let saved = self.read(key)?;
+                let value = serde_json::from_str(&saved)?;
+                let _ = slot.saved.set(saved);
+
+                Ok((value, <K::Field as Field<K>>::WorkingValues::default()))
+            })
+            .ok();
+        if loaded.is_none() {
+            slot.broken.set(true);
+            self.broken.set(self.broken.get() + 1);
+        }
+
+        loaded
+    }
+
+    /// Read an object from the database, as JSON
+    fn read(&self, key: &K::Key) -> Result<String, StoreError> {
+        let conn = self.conn.as_ref().ok_or("document hasn't been saved")?;
+        let value: String = conn.query_row(
+            "SELECT value FROM objects WHERE key = ?",
+            params![serde_json::to_string(key)?],
+            |row| row.get(0),
+        )?;
+
+        Ok(value)
+    }
+
+    /// Copy what's been saved so far to a new database at `path`, which mustn't exist yet.
+    fn copy_to(&self, path: &Path) -> Result<(), StoreError> {
+        match &self.conn {
+            Some(conn) => {
+                conn.execute(
+                    "VACUUM INTO ?",
+                    params![path.to_string_lossy().into_owned()],
+                )?;
+            }
+            None => {
+                Connection::open(path)?.execute(CREATE_TABLE, NO_PARAMS)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write every change since the last save to the current database, in one transaction.
+    fn write_back(&mut self) -> Result<(), StoreError> {
+        let conn = self.conn.as_mut().ok_or("document hasn't been saved")?;
+        let tx = conn.transaction()?;
+        for key in self.deleted.iter() {
+            tx.execute(
+                "DELETE FROM objects WHERE key = ?",
+                params![serde_json::to_string(key)?],
+            )?;
+        }
+
+        // Objects which are written are kept here until the transaction succeeds
+        let mut written = vec![];
+        for (position, key) in self.order.iter().enumerate() {
+            let slot = &self.slots[key];
+            let changed = match slot.value.get() {
+                Some((value, _)) if slot.dirty => {
+                    let value = serde_json::to_string(value)?;
+                    if slot.saved.get() != Some(&value) {
+                        Some(value)
+                    } else {
+                        None
+                    }
+                }
+                _ => None,
+            };
+
+            match changed {
+                Some(value) => {
+                    tx.execute(
+                        "INSERT OR REPLACE INTO objects (key, position, value) VALUES (?, ?, ?)",
+                        params![serde_json::to_string(key)?, position as i64, value],
+                    )?;
+                    written.push((key.clone(), value));
+                }
+                None if self.order_dirty => {
+                    tx.execute(
+                        "UPDATE objects SET position = ? WHERE key = ?",
+                        params![position as i64, serde_json::to_string(key)?],
+                    )?;
+                }
+                _ => (),
+            }
+        }
+        tx.commit()?;
+
+        // Only forget about changes once they're definitely written
+        self.deleted.clear();
+        self.order_dirty = false;
+        for slot in self.slots.values_mut() {
+            slot.dirty = false;
+        }
+        for (key, value) in written {
+            if let Some(slot) = self.slots.get_mut(&key) {
+                slot.saved = OnceCell::from(value);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<K> ObjectStore<K> for SqliteContainer<K>
+where
+    K: Kind + Serialize + DeserializeOwned,
+    K::Key: Ord + Serialize + DeserializeOwned,
+{
+    /// Create a store for a new document. It's kept in memory until it's first saved.
+    fn empty() -> Self {
+        Self {
+            conn: None,
+            path: None,
+            slots: BTreeMap::new(),
+            order: vec![],
+            deleted: vec![],
+            order_dirty: false,
+            broken: Cell::new(0),
+            last: None,
+        }
+    }
+
+    fn insert_with(&mut self, value: K) -> &K::Key {
+        let key = self.next_key();
+        self.insert_at(self.order.len(), key, value);
+
+        self.order.last().unwrap()
+    }
+
+    fn insert_with_key(&mut self, key: K::Key, value: K) -> bool {
+        if self.slots.contains_key(&key) {
+            return false;
+        }
+
+        self.last = Some(key.clone());
+        self.insert_at(self.order.len(), key, value);
+
+        true
+    }
+
+    /// Objects that can't be read are still removed, but there's nothing to give back so `None` is returned.
+    fn remove(&mut self, key: &K::Key) -> Option<Loaded<K>> {
+        // Read it if we can, so it can be given back
+        self.load(key);
+        let slot = self.slots.remove(key)?;
+        if slot.broken.get() {
+            self.broken.set(self.broken.get() - 1);
+        }
+        if let Some(i) = self.position(key) {
+            self.order.remove(i);
+        }
+        self.deleted.push(key.clone());
+        self.order_dirty = true;
+
+        slot.value.into_inner()
+    }
+
+    fn duplicate(&mut self, key: &K::Key) -> Option<&K::Key> {
+        let value = self.load(key)?.0.clone();
+        let i = self.position(key)?;
+        let next = self.next_key();
+        self.insert_at(i + 1, next, value);
+        self.order_dirty = true;
+
+        Some(&self.order[i + 1])
+    }
+
+    fn move_to(&mut self, key: &K::Key, index: usize) -> bool {
+        match self.position(key) {
+            Some(i) => {
+                let key = self.order.remove(i);
+                let index = self.order_index(index);
+                self.order.insert(index, key);
+                self.order_dirty = true;
+
+                true
+            }
+            None => false,
+        }
+    }
+
+    type Items<'a> = SqliteItems<'a, K>;
+    fn items<'a>(&'a self) -> Self::Items<'a> {
+        SqliteItems {
+            keys: self.order.iter(),
+            store: self,
+        }
+    }
+
+    type Keys<'a> = SqliteKeys<'a, K>;
+    fn keys<'a>(&'a self) -> Self::Keys<'a> {
+        SqliteKeys {
+            keys: self.order.iter(),
+            store: self,
+        }
+    }
+
+    type Values<'a> = SqliteValues<'a, K>;
+    fn values<'a>(&'a self) -> Self::Values<'a> {
+        SqliteValues(self.items())
+    }
+
+    fn get(&self, key: &K::Key) -> Option<(&K, &<K::Field as Field<K>>::WorkingValues)> {
+        self.load(key).map(|(v, w)| (v, w))
+    }
+
+    fn get_mut(
+        &mut self,
+        key: &K::Key,
+    ) -> Option<(&mut K, &mut <K::Field as Field<K>>::WorkingValues)> {
+        self.load(key)?;
+        let slot = self.slots.get_mut(key)?;
+        slot.dirty = true;
+
+        slot.value.get_mut().map(|(v, w)| (v, w))
+    }
+
+    fn exists(&self, key: &K::Key) -> bool {
+        self.slots.contains_key(key) && !self.is_broken(key)
+    }
+
+    fn count(&self) -> usize {
+        self.order.len() - self.broken.get()
+    }
+
+    fn open(path: &Path) -> Option<Result<Self, StoreError>> {
+        Some(Self::open_file(path))
+    }
+
+    /// Write back changes. Saving somewhere new copies the database there first, and carries on using the copy.
+    /// Whatever was there before is only replaced once the copy has been made.
+    fn save(&mut self, path: &Path) -> Option<Result<(), StoreError>> {
+        Some((|| {
+            let path = canonical(path)?;
+            if self.path.as_ref() != Some(&path) {
+                let temp = temp_path(&path)?;
+                if let Err(e) = self.copy_to(&temp) {
+                    let _ = fs::remove_file(&temp);
+                    return Err(e);
+                }
+                fs::rename(&temp, &path)?;
+
+                self.conn = Some(Connection::open_with_flags(
+                    &path,
+                    OpenFlags::SQLITE_OPEN_READ_WRITE,
+                )?);
+                self.path = Some(path);
+            }
+
+            self.write_back()
+        })())
+    }
+}
+
+/// Get the canonical form of a path, so that different ways of writing it can be compared.
+/// The file doesn't have to exist, but the directory it's in does.
+fn canonical(path: &Path) -> io::Result<PathBuf> {
+    match fs::canonicalize(path) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            let name = path
+                .file_name()
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "not a file"))?;
+            let dir = match path.parent() {
+                Some(dir) if !dir.as_os_str().is_empty() => dir,
+                _ => Path::new("."),
+            };
+
+            Ok(fs::canonicalize(dir)?.join(name))
+        }
+        r => r,
+    }
+}
+
+/// Get somewhere to make a copy of a database before moving it to `path`.
+/// It's in the same directory, so the move doesn't have to copy it again, and doesn't exist yet.
+fn temp_path(path: &Path) -> io::Result<PathBuf> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let temp = path.with_file_name(format!(".{}.{}.tmp", name, std::process::id()));
+    match fs::remove_file(&temp) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(temp),
+    }
+}
+
+/// Iterates over the keys in a [`SqliteContainer`], in order, skipping objects that couldn't be read.
+pub struct SqliteKeys<'a, K: Kind> {
+    keys: std::slice::Iter<'a, K::Key>,
+    store: &'a SqliteContainer<K>,
+}
+
+impl<'a, K> Iterator for SqliteKeys<'a, K>
+where
+    K: Kind + Serialize + DeserializeOwned,
+    K::Key: Ord + Serialize + DeserializeOwned,
+{
+    type Item = &'a K::Key;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let store = self.store;
+        self.keys.find(|k| !store.is_broken(k))
+    }
+}
+
+/// Iterates over the objects in a [`SqliteContainer`], in order, reading them as needed.
+pub struct SqliteItems<'a, K: Kind> {
+    keys: std::slice::Iter<'a, K::Key>,
+    store: &'a SqliteContainer<K>,
+}
+
+impl<'a, K> Iterator for SqliteItems<'a, K>
+where
+    K: Kind + Serialize + DeserializeOwned,
+    K::Key: Ord + Serialize + DeserializeOwned,
+{
+    type Item = (&'a K::Key, &'a K, &'a <K::Field as Field<K>>::WorkingValues);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Skip over anything that can't be read
+        for k in &mut self.keys {
+            if let Some((v, w)) = self.store.load(k) {
+                return Some((k, v, w));
+            }
+        }
+
+        None
+    }
+}
+
+/// Iterates over the values in a [`SqliteContainer`], in order.
+pub struct SqliteValues<'a, K: Kind>(SqliteItems<'a, K>);
+
+impl<'a, K> Iterator for SqliteValues<'a, K>
+where
+    K: Kind + Serialize + DeserializeOwned,
+    K::Key: Ord + Serialize + DeserializeOwned,
+{
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(_k, v, _w)| v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Thing;
+    use tempfile::TempDir;
+
+    fn names(store: &SqliteContainer<Thing>) -> Vec<&str> {
+        store.values().map(|t| t.name.as_str()).collect()
+    }
+
+    fn keys(store: &SqliteContainer<Thing>) -> Vec<u32> {
+        store.keys().copied().collect()
+    }
+
+    /// Save a new document with objects named "a", "b" and "c", with keys 0, 1 and 2
+    fn abc(dir: &TempDir) -> PathBuf {
+        let path = dir.path().join("abc.sqlite");
+        let mut store = SqliteContainer::<Thing>::empty();
+        for name in ["a", "b", "c"].iter() {
+            store.insert_with(Thing::new(name, 0));
+        }
+        store.save(&path).unwrap().unwrap();
+
+        path
+    }
+
+    #[test]
+    fn round_trip() {
+        let dir = TempDir::new().unwrap();
+        let path = abc(&dir);
+
+        let mut store = SqliteContainer::<Thing>::open(&path).unwrap().unwrap();
+        assert_eq!(vec!["a", "b", "c"], names(&store));
+
+        store.get_mut(&0).unwrap().0.size = 5;
+        store.remove(&1);
+        assert_eq!(Some(&3), store.duplicate(&2));
+        assert!(store.move_to(&2, 0));
+        assert_eq!(4, *store.insert_with(Thing::new("d", 0)));
+        store.save(&path).unwrap().unwrap();
+
+        let store = SqliteContainer::<Thing>::open(&path).unwrap().unwrap();
+        assert_eq!(vec![2, 0, 3, 4], keys(&store));
+        assert_eq!(vec!["c", "a", "c", "d"], names(&store));
+        assert_eq!(5, store.get(&0).unwrap().0.size);
+        assert!(!store.exists(&1));
+        assert_eq!(4, store.count());
+    }
+
+    #[test]
+    fn save_as() {
+        let dir = TempDir::new().unwrap();
+        let path = abc(&dir);
+        let copy = dir.path().join("copy.sqlite");
+
+        let mut store = SqliteContainer::<Thing>::open(&path).unwrap().unwrap();
+        store.remove(&0);
+        store.save(&copy).unwrap().unwrap();
+
+        // Later saves go to the copy
+        store.insert_with(Thing::new("d", 0));
+        store.save(&copy).unwrap().unwrap();
+
+        let original = SqliteContainer::<Thing>::open(&path).unwrap().unwrap();
+        assert_eq!(vec!["a", "b", "c"], names(&original));
+        let copy = SqliteContainer::<Thing>::open(&copy).unwrap().unwrap();
+        assert_eq!(vec!["b", "c", "d"], names(&copy));
+    }
+
+    #[test]
+    fn insert_with_key() {
+        let mut store = SqliteContainer::<Thing>::empty();
+        assert!(store.insert_with_key(1, Thing::new("b", 0)));
+        assert!(store.insert_with_key(0, Thing::new("a", 0)));
+        assert!(!store.insert_with_key(1, Thing::new("c", 0)));
+        assert_eq!(vec!["b", "a"], names(&store));
+
+        // Counting on from 0 would hit 1, which is taken
+        assert_eq!(&2, store.insert_with(Thing::new("c", 0)));
+    }
+
+    #[test]
+    fn save_as_existing_file() {
+        let dir = TempDir::new().unwrap();
+        let path = abc(&dir);
+        let other = dir.path().join("other.sqlite");
+        fs::write(&other, "not a database").unwrap();
+
+        let mut store = SqliteContainer::<Thing>::open(&path).unwrap().unwrap();
+        store.remove(&0);
+        store.save(&other).unwrap().unwrap();
+
+        let other = SqliteContainer::<Thing>::open(&other).unwrap().unwrap();
+        assert_eq!(vec!["b", "c"], names(&other));
+    }
+
+    #[test]
+    fn save_to_same_file_written_differently() {
+        let dir = TempDir::new().unwrap();
+        let path = abc(&dir);
+        let same = dir.path().join(".").join("abc.sqlite");
+
+        let mut store = SqliteContainer::<Thing>::open(&path).unwrap().unwrap();
+        store.remove(&0);
+        store.insert_with(Thing::new("d", 0));
+        store.save(&same).unwrap().unwrap();
+
+        // Later saves still go to the same file
+        store.get_mut(&1).unwrap().0.size = 5;
+        store.save(&path).unwrap().unwrap();
+
+        let store = SqliteContainer::<Thing>::open(&path).unwrap().unwrap();
+        assert_eq!(vec!["b", "c", "d"], names(&store));
+        assert_eq!(5, store.get(&1).unwrap().0.size);
+    }
+
+    #[test]
+    fn only_changed_objects_written() {
+        let dir = TempDir::new().unwrap();
+        let path = abc(&dir);
+
+        let mut store = SqliteContainer::<Thing>::open(&path).unwrap().unwrap();
+        *store.get_mut(&0).unwrap().1 = Some("draft".to_string());
+        store.get_mut(&1).unwrap().0.size = 5;
+
+        // If the untouched object was written back, this would be overwritten
+        let changed = serde_json::to_string(&Thing::new("changed", 0)).unwrap();
+        Connection::open(&path)
+            .unwrap()
+            .execute(
+                "UPDATE objects SET value = ? WHERE key = '0'",
+                params![changed],
+            )
+            .unwrap();
+        store.save(&path).unwrap().unwrap();
+
+        let store = SqliteContainer::<Thing>::open(&path).unwrap().unwrap();
+        assert_eq!(vec!["changed", "b", "c"], names(&store));
+        assert_eq!(5, store.get(&1).unwrap().0.size);
+    }
+
+    #[test]
+    fn unsaved_changes_not_written() {
+        let dir = TempDir::new().unwrap();
+        let path = abc(&dir);
+
+        let mut store = SqliteContainer::<Thing>::open(&path).unwrap().unwrap();
+        store.remove(&0);
+        store.insert_with(Thing::new("d", 0));
+        drop(store);
+
+        let store = SqliteContainer::<Thing>::open(&path).unwrap().unwrap();
+        assert_eq!(vec!["a", "b", "c"], names(&store));
+    }
+
+    #[test]
+    fn broken_rows() {
+        let dir = TempDir::new().unwrap();
+        let path = abc(&dir);
+        Connection::open(&path)
+            .unwrap()
+            .execute("UPDATE objects SET value = '{' WHERE key = '1'", NO_PARAMS)
+            .unwrap();
+
+        let mut store = SqliteContainer::<Thing>::open(&path).unwrap().unwrap();
+        assert!(store.get(&1).is_none());
+        assert_eq!(vec![0, 2], keys(&store));
+        assert_eq!(store.items().count(), store.count());
+        assert!(!store.exists(&1));
+
+        // Moves are by position among the objects that can be read
+        assert!(store.move_to(&0, 1));
+        assert_eq!(vec![2, 0], keys(&store));
+
+        assert!(store.remove(&1).is_none());
+        store.save(&path).unwrap().unwrap();
+
+        let store = SqliteContainer::<Thing>::open(&path).unwrap().unwrap();
+        assert_eq!(vec![2, 0], keys(&store));
+        assert_eq!(2, store.count());
+    }
+}
@@ -1,43 +1,122 @@
 use crate::{Field, Key, Kind, ObjectStore};
 
+/// An object in a [`VecContainer`], with its key and working values
+type Entry<K> = (
+    <K as Kind>::Key,
+    K,
+    <<K as Kind>::Field as Field<K>>::WorkingValues,
+);
+
 /// Vector backed container
-pub struct VecContainer<K: Kind>(Vec<(K::Key, K, <K::Field as Field>::WorkingValues)>);
-impl<E: Key + Ord, K: Kind<Key = E>> ObjectStore<K> for VecContainer<K> {
-    type Items<'a> =
-        PopTupleRefs<std::slice::Iter<'a, (K::Key, K, <K::Field as Field>::WorkingValues)>>;
+pub struct VecContainer<K: Kind> {
+    objects: Vec<Entry<K>>,
+
+    /// The last key we allocated, to pass to [`Key::next`]
+    last: Option<K::Key>,
+}
+
+impl<K: Kind> VecContainer<K> {
+    /// Get the index of the object with the given key
+    fn position(&self, key: &K::Key) -> Option<usize> {
+        self.objects.iter().position(|(k, _, _)| k == key)
+    }
+
+    /// Get a key that isn't in use yet.
+    /// Keys inserted by [`ObjectStore::insert_with_key`] might be in the way, so those are skipped.
+    fn next_key(&mut self) -> K::Key {
+        let mut next = match &self.last {
+            Some(last) => K::Key::next(last),
+            None => K::Key::first(),
+        };
+        while self.exists(&next) {
+            next = K::Key::next(&next);
+        }
+        self.last = Some(next.clone());
+
+        next
+    }
+}
+impl<K: Kind> ObjectStore<K> for VecContainer<K> {
+    type Items<'a> = PopTupleRefs<std::slice::Iter<'a, Entry<K>>>;
     fn items<'a>(&'a self) -> Self::Items<'a> {
-        PopTupleRefs::new(self.0.iter())
+        PopTupleRefs::new(self.objects.iter())
     }
     fn empty() -> Self {
-        Self(vec![])
+        Self {
+            objects: vec![],
+            last: None,
+        }
     }
 
     fn exists(&self, key: &K::Key) -> bool {
-        self.0.iter().find(|(k, _, _)| k == key).is_some()
+        self.objects.iter().find(|(k, _, _)| k == key).is_some()
     }
 
     fn count(&self) -> usize {
-        self.0.len()
+        self.objects.len()
     }
 
-    fn add(&mut self) -> &K::Key {
-        let last = self.0.iter().map(|(k, _, _)| k).max();
-        let next = if let Some(last) = last {
-            K::Key::next(last)
-        } else {
-            K::Key::first()
-        };
-        self.0.push((
+    fn insert_with(&mut self, value: K) -> &K::Key {
+        let next = self.next_key();
+        self.objects.push((
             next,
-            K::default(),
-            <K::Field as Field>::WorkingValues::default(),
+            value,
+            <K::Field as Field<K>>::WorkingValues::default(),
         ));
 
-        &self.0.last().unwrap().0
+        &self.objects.last().unwrap().0
+    }
+
+    fn insert_with_key(&mut self, key: K::Key, value: K) -> bool {
+        if self.exists(&key) {
+            return false;
+        }
+
+        self.last = Some(key.clone());
+        self.objects
+            .push((key, value, <K::Field as Field<K>>::WorkingValues::default()));
+
+        true
+    }
+
+    fn remove(&mut self, key: &K::Key) -> Option<(K, <K::Field as Field<K>>::WorkingValues)> {
+        let i = self.position(key)?;
+        let (_, v, w) = self.objects.remove(i);
+
+        Some((v, w))
+    }
+
+    fn duplicate(&mut self, key: &K::Key) -> Option<&K::Key> {
+        let i = self.position(key)?;
+        let value = self.objects[i].1.clone();
+        let next = self.next_key();
+        self.objects.insert(
+            i + 1,
+            (
+                next,
+                value,
+                <K::Field as Field<K>>::WorkingValues::default(),
+            ),
+        );
+
+        Some(&self.objects[i + 1].0)
+    }
+
+    fn move_to(&mut self, key: &K::Key, index: usize) -> bool {
+        match self.position(key) {
+            Some(i) => {
+                let item = self.objects.remove(i);
+                let index = index.min(self.objects.len());
+                self.objects.insert(index, item);
+
+                true
+            }
+            None => false,
+        }
     }
 
-    fn get(&self, key: &K::Key) -> Option<(&K, &<K::Field as Field>::WorkingValues)> {
-        self.0
+    fn get(&self, key: &K::Key) -> Option<(&K, &<K::Field as Field<K>>::WorkingValues)> {
+        self.objects
             .iter()
             .find(|(k, _, _)| k == key)
             .map(|(_, v, w)| (v, w))
@@ -46,25 +125,23 @@ impl<E: Key + Ord, K: Kind<Key = E>> ObjectStore<K> for VecContainer<K> {
     fn get_mut(
         &mut self,
         key: &K::Key,
-    ) -> Option<(&mut K, &mut <K::Field as Field>::WorkingValues)> {
-        self.0
+    ) -> Option<(&mut K, &mut <K::Field as Field<K>>::WorkingValues)> {
+        self.objects
             .iter_mut()
             .find(|(k, _, _)| k == key)
             .map(|(_, v, w)| (v, w))
     }
 
-    type Keys<'a> =
-        FirstTupleElem<std::slice::Iter<'a, (K::Key, K, <K::Field as Field>::WorkingValues)>>;
+    type Keys<'a> = FirstTupleElem<std::slice::Iter<'a, Entry<K>>>;
 
     fn keys<'a>(&'a self) -> Self::Keys<'a> {
-        FirstTupleElem::new(self.0.iter())
+        FirstTupleElem::new(self.objects.iter())
     }
 
-    type Values<'a> =
-        SecondTupleElem<std::slice::Iter<'a, (K::Key, K, <K::Field as Field>::WorkingValues)>>;
+    type Values<'a> = SecondTupleElem<std::slice::Iter<'a, Entry<K>>>;
 
     fn values<'a>(&'a self) -> Self::Values<'a> {
-        SecondTupleElem::new(self.0.iter())
+        SecondTupleElem::new(self.objects.iter())
     }
 }
 
@@ -112,3 +189,81 @@ impl<'a, A: 'a, B: 'a, C: 'a, T: Iterator<Item = &'a (A, B, C)>> Iterator for Se
         self.0.next().map(|(_a, b, _c)| b)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Thing;
+
+    /// A store with objects named "a", "b" and "c", with keys 0, 1 and 2
+    fn abc() -> VecContainer<Thing> {
+        let mut store = VecContainer::empty();
+        for name in ["a", "b", "c"].iter() {
+            store.insert_with(Thing::new(name, 0));
+        }
+
+        store
+    }
+
+    fn names(store: &VecContainer<Thing>) -> Vec<&str> {
+        store.values().map(|t| t.name.as_str()).collect()
+    }
+
+    #[test]
+    fn insert_with_key() {
+        let mut store = VecContainer::empty();
+        assert!(store.insert_with_key(1, Thing::new("b", 0)));
+        assert!(store.insert_with_key(0, Thing::new("a", 0)));
+        assert!(!store.insert_with_key(1, Thing::new("c", 0)));
+        assert_eq!(vec!["b", "a"], names(&store));
+
+        // Counting on from 0 would hit 1, which is taken
+        assert_eq!(&2, store.insert_with(Thing::new("c", 0)));
+    }
+
+    #[test]
+    fn remove() {
+        let mut store = abc();
+        *store.get_mut(&1).unwrap().1 = Some("working".to_string());
+
+        let (thing, working) = store.remove(&1).unwrap();
+        assert_eq!("b", thing.name);
+        assert_eq!(Some("working".to_string()), working);
+        assert_eq!(vec!["a", "c"], names(&store));
+        assert!(!store.exists(&1));
+        assert!(store.remove(&1).is_none());
+    }
+
+    #[test]
+    fn keys_not_reused() {
+        let mut store = abc();
+        store.remove(&2);
+
+        assert_eq!(3, *store.add());
+    }
+
+    #[test]
+    fn duplicate() {
+        let mut store = abc();
+        *store.get_mut(&0).unwrap().1 = Some("working".to_string());
+
+        assert_eq!(Some(&3), store.duplicate(&0));
+        assert_eq!(vec!["a", "a", "b", "c"], names(&store));
+        assert_eq!(vec![0, 3, 1, 2], store.keys().copied().collect::<Vec<_>>());
+        assert_eq!(&None, store.get(&3).unwrap().1);
+        assert!(store.duplicate(&9).is_none());
+    }
+
+    #[test]
+    fn move_to() {
+        let mut store = abc();
+
+        assert!(store.move_to(&0, 2));
+        assert_eq!(vec!["b", "c", "a"], names(&store));
+        assert!(store.move_to(&0, 0));
+        assert_eq!(vec!["a", "b", "c"], names(&store));
+        assert!(store.move_to(&1, 100));
+        assert_eq!(vec!["a", "c", "b"], names(&store));
+        assert!(!store.move_to(&9, 0));
+    }
+}
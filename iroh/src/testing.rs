@@ -0,0 +1,44 @@
+//! A small kind for unit tests to edit.
+// Which tests are built depends on the features enabled, so not everything here is always used.
+#![allow(dead_code)]
+
+use crate::{fields::TextInputField, lens::Lens, Kind};
+
+/// A named thing, edited with a text input for its name. It can refer to another thing as its parent.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Thing {
+    pub name: String,
+    pub size: u32,
+    pub parent: Option<u32>,
+}
+
+impl Thing {
+    pub fn new(name: &str, size: u32) -> Self {
+        Self {
+            name: name.to_string(),
+            size,
+            parent: None,
+        }
+    }
+}
+
+impl Kind for Thing {
+    type Key = u32;
+    type Field = TextInputField<"Name", ThingNameLens>;
+}
+
+#[derive(Debug, Clone)]
+pub struct ThingNameLens;
+impl Lens for ThingNameLens {
+    type Source = Thing;
+    type Target = String;
+
+    fn get(source: &Self::Source) -> &Self::Target {
+        &source.name
+    }
+
+    fn get_mut(source: &mut Self::Source) -> &mut Self::Target {
+        &mut source.name
+    }
+}
@@ -1,6 +1,10 @@
 //! Provides common appearance presets.
 
-use iced::{button, container, pane_grid, text_input, Background, Color, Vector};
+use iced::{
+    button, checkbox, container, pane_grid, pick_list, slider, text_input, Background, Color,
+    Vector,
+};
+use iced_style::menu;
 
 /// Represents the currently in-use theme. This provides a way to get colours semantically, as in by their purpose.
 #[derive(Debug, Clone)]
@@ -24,6 +28,11 @@ impl Theme {
         dark::TEXT_ACCENT
     }
 
+    /// To be used for text pointing out a problem, such as invalid input, when on bg_primary
+    pub fn text_error(&self) -> Color {
+        dark::TEXT_ERROR
+    }
+
     /// To be used for text displayed on bg_accent
     pub fn text_on_accent(&self) -> Color {
         dark::TEXT_ON_ACCENT
@@ -94,6 +103,90 @@ impl Theme {
             }),
         }
     }
+
+    /// Style for text inputs whose contents aren't valid
+    pub fn text_input_error(&self) -> Box<dyn text_input::StyleSheet> {
+        match self {
+            Theme::Dark => Box::new(TextInputStyle {
+                bg: dark::BACKGROUND_PRIMARY,
+                border_normal: dark::TEXT_ERROR,
+                border_focused: dark::TEXT_ERROR,
+                text: dark::TEXT_PRIMARY,
+            }),
+        }
+    }
+
+    /// Style for the box around multiline text inputs, which matches [`Self::text_input`]
+    pub fn text_area(&self, focused: bool) -> Box<dyn container::StyleSheet> {
+        match self {
+            Theme::Dark => Box::new(TextAreaStyle {
+                bg: dark::BACKGROUND_PRIMARY,
+                border: if focused {
+                    dark::BACKGROUND_ACCENT
+                } else {
+                    mult(dark::BACKGROUND_PRIMARY, 1.1)
+                },
+                text: dark::TEXT_PRIMARY,
+            }),
+        }
+    }
+
+    /// Style for the line showing where the cursor is in a multiline text input
+    pub fn caret(&self) -> Box<dyn container::StyleSheet> {
+        match self {
+            Theme::Dark => Box::new(ContainerStyle {
+                text: dark::TEXT_PRIMARY,
+                bg: dark::TEXT_PRIMARY,
+            }),
+        }
+    }
+
+    /// Style for checkboxes
+    pub fn checkbox(&self) -> Box<dyn checkbox::StyleSheet> {
+        match self {
+            Theme::Dark => Box::new(CheckboxStyle {
+                bg: dark::BACKGROUND_PRIMARY,
+                border: mult(dark::BACKGROUND_PRIMARY, 1.1),
+                checked: dark::BACKGROUND_ACCENT,
+                check: dark::TEXT_PRIMARY,
+            }),
+        }
+    }
+
+    /// Style for sliders
+    pub fn slider(&self) -> Box<dyn slider::StyleSheet> {
+        match self {
+            Theme::Dark => Box::new(SliderStyle {
+                rail: mult(dark::BACKGROUND_PRIMARY, 1.5),
+                handle: dark::BACKGROUND_ACCENT,
+                active: dark::TEXT_ACCENT,
+            }),
+        }
+    }
+
+    /// Style for dropdowns
+    pub fn pick_list(&self) -> Box<dyn pick_list::StyleSheet> {
+        match self {
+            Theme::Dark => Box::new(PickListStyle {
+                bg: dark::BACKGROUND_PRIMARY,
+                border_normal: mult(dark::BACKGROUND_PRIMARY, 1.1),
+                border_hovered: dark::BACKGROUND_ACCENT,
+                text: dark::TEXT_PRIMARY,
+                selected_bg: dark::BACKGROUND_ACCENT,
+                selected_text: dark::TEXT_ON_ACCENT,
+            }),
+        }
+    }
+
+    /// Style for a container showing a sample of the given colour
+    pub fn swatch(&self, color: Color) -> Box<dyn container::StyleSheet> {
+        match self {
+            Theme::Dark => Box::new(SwatchStyle {
+                fill: color,
+                border: mult(dark::BACKGROUND_PRIMARY, 1.5),
+            }),
+        }
+    }
 }
 
 impl Into<Box<dyn pane_grid::StyleSheet>> for &Theme {
@@ -128,6 +221,23 @@ impl container::StyleSheet for ContainerStyle {
     }
 }
 
+/// A style for colour swatches
+struct SwatchStyle {
+    fill: Color,
+    border: Color,
+}
+impl container::StyleSheet for SwatchStyle {
+    fn style(&self) -> container::Style {
+        container::Style {
+            text_color: None,
+            background: Some(Background::Color(self.fill)),
+            border_radius: 0.0,
+            border_width: 1.0,
+            border_color: self.border,
+        }
+    }
+}
+
 /// A style we apply to buttons
 struct ButtonStyle {
     bg: Color,
@@ -208,6 +318,124 @@ impl text_input::StyleSheet for TextInputStyle {
     }
 }
 
+struct TextAreaStyle {
+    bg: Color,
+    border: Color,
+    text: Color,
+}
+impl container::StyleSheet for TextAreaStyle {
+    fn style(&self) -> container::Style {
+        container::Style {
+            text_color: Some(self.text),
+            background: Some(Background::Color(self.bg)),
+            border_radius: 0.0,
+            border_width: 1.0,
+            border_color: self.border,
+        }
+    }
+}
+
+struct CheckboxStyle {
+    bg: Color,
+    border: Color,
+    checked: Color,
+    check: Color,
+}
+impl checkbox::StyleSheet for CheckboxStyle {
+    fn active(&self, is_checked: bool) -> checkbox::Style {
+        checkbox::Style {
+            background: Background::Color(if is_checked { self.checked } else { self.bg }),
+            checkmark_color: self.check,
+            border_radius: 0.0,
+            border_width: 1.0,
+            border_color: self.border,
+        }
+    }
+
+    fn hovered(&self, is_checked: bool) -> checkbox::Style {
+        checkbox::Style {
+            border_color: self.checked,
+            ..self.active(is_checked)
+        }
+    }
+}
+
+struct SliderStyle {
+    rail: Color,
+    handle: Color,
+    active: Color,
+}
+impl slider::StyleSheet for SliderStyle {
+    fn active(&self) -> slider::Style {
+        slider::Style {
+            rail_colors: (self.rail, self.rail),
+            handle: slider::Handle {
+                shape: slider::HandleShape::Rectangle {
+                    width: 8,
+                    border_radius: 0.0,
+                },
+                color: self.handle,
+                border_width: 0.0,
+                border_color: self.handle,
+            },
+        }
+    }
+
+    fn hovered(&self) -> slider::Style {
+        let mut s = self.active();
+        s.handle.border_width = 1.0;
+        s.handle.border_color = self.active;
+
+        s
+    }
+
+    fn dragging(&self) -> slider::Style {
+        let mut s = self.active();
+        s.handle.color = self.active;
+
+        s
+    }
+}
+
+struct PickListStyle {
+    bg: Color,
+    border_normal: Color,
+    border_hovered: Color,
+    text: Color,
+    selected_bg: Color,
+    selected_text: Color,
+}
+impl pick_list::StyleSheet for PickListStyle {
+    fn menu(&self) -> menu::Style {
+        menu::Style {
+            text_color: self.text,
+            background: Background::Color(self.bg),
+            border_width: 1.0,
+            border_color: self.border_normal,
+            selected_text_color: self.selected_text,
+            selected_background: Background::Color(self.selected_bg),
+        }
+    }
+
+    fn active(&self) -> pick_list::Style {
+        pick_list::Style {
+            text_color: self.text,
+            background: Background::Color(self.bg),
+            border_radius: 0.0,
+            border_width: 1.0,
+            border_color: self.border_normal,
+            icon_size: 0.7,
+        }
+    }
+
+    fn hovered(&self) -> pick_list::Style {
+        pick_list::Style {
+            border_color: self.border_hovered,
+            ..self.active()
+        }
+    }
+}
+
 fn mult(c: Color, f: f32) -> Color {
     let a = c.into_linear();
 
@@ -222,6 +450,8 @@ mod dark {
     pub const TEXT_ACCENT: Color =
         Color::from_rgba(0.39215686274, 0.86666666666, 0.09019607843, 1.0);
     pub const TEXT_ON_ACCENT: Color = Color::from_rgba(1.0, 1.0, 1.0, 0.5);
+    pub const TEXT_ERROR: Color =
+        Color::from_rgba(0.94117647058, 0.32549019607, 0.31372549019, 1.0);
     pub const BACKGROUND_PRIMARY: Color =
         Color::from_rgba(0.1294117647, 0.1294117647, 0.1294117647, 1.0);
     pub const BACKGROUND_ACCENT: Color =
@@ -0,0 +1,64 @@
+//! The bar along the top of the editor, with document-wide actions.
+
+use crate::{app::AppState, message::Message, Kind, ObjectStore};
+use iced::{button, Align, Button, Container, Element, Length, Row, Space, Text};
+
+/// Buttons for opening & saving, and a place to report how that went.
+pub struct Toolbar {
+    open_state: button::State,
+    save_state: button::State,
+    save_as_state: button::State,
+}
+
+impl Toolbar {
+    /// Create a new toolbar
+    pub fn new() -> Self {
+        Self {
+            open_state: button::State::default(),
+            save_state: button::State::default(),
+            save_as_state: button::State::default(),
+        }
+    }
+
+    /// Get what to currently render, with the given status message on the right.
+    pub fn view<K: Kind, C: ObjectStore<K>>(
+        &mut self,
+        app_state: &AppState<K, C>,
+        status: Option<&str>,
+    ) -> Element<Message<K>> {
+        let theme = app_state.theme();
+
+        let row = Row::with_children(vec![
+            Button::new(&mut self.open_state, Text::new("Open"))
+                .on_press(Message::Open(None))
+                .style(theme.button_subtle())
+                .into(),
+            Button::new(&mut self.save_state, Text::new("Save"))
+                .on_press(Message::Save)
+                .style(theme.button_subtle())
+                .into(),
+            Button::new(&mut self.save_as_state, Text::new("Save As"))
+                .on_press(Message::SaveAs(None))
+                .style(theme.button_subtle())
+                .into(),
+            Space::with_width(Length::Fill).into(),
+            Text::new(status.unwrap_or(""))
+                .color(theme.text_primary())
+                .into(),
+        ])
+        .spacing(4)
+        .padding(4)
+        .align_items(Align::Center);
+
+        Container::new(row)
+            .width(Length::Fill)
+            .style(theme.container_primary())
+            .into()
+    }
+}
+
+impl Default for Toolbar {
+    fn default() -> Self {
+        Self::new()
+    }
+}